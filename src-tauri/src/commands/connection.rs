@@ -1,5 +1,7 @@
 use crate::commands::TauriState;
-use crate::models::ConnectionConfig;
+use crate::db::ssh::{self, KnownHostsStore};
+use crate::models::{ConnectionConfig, SshHostKey, TunnelStatus};
+use std::collections::HashMap;
 use tauri::State;
 
 #[tauri::command]
@@ -18,12 +20,28 @@ pub async fn disconnect(id: String, state: State<'_, TauriState>) -> Result<(),
 }
 
 #[tauri::command]
-pub async fn test_connection(
+pub async fn get_tunnel_status(
+    id: String,
+    state: State<'_, TauriState>,
+) -> Result<Option<TunnelStatus>, String> {
+    Ok(state.db.get_tunnel_status(&id).await)
+}
+
+#[tauri::command]
+pub async fn list_ssh_tunnels(
+    state: State<'_, TauriState>,
+) -> Result<HashMap<String, TunnelStatus>, String> {
+    Ok(state.db.list_tunnels().await)
+}
+
+// The editor sends empty secrets when they were left untouched; fill them from the
+// saved connection.
+fn with_saved_secrets(
     config: ConnectionConfig,
     connection_id: Option<String>,
-    state: State<'_, TauriState>,
-) -> Result<String, String> {
-    let mut final_config = config.clone();
+    state: &TauriState,
+) -> ConnectionConfig {
+    let mut final_config = config;
     if let Some(cid) = connection_id {
         if let Some(saved) = state.storage.get_connection(&cid) {
             if final_config
@@ -42,8 +60,51 @@ pub async fn test_connection(
             {
                 final_config.ssh_password = saved.ssh_password;
             }
+            if final_config
+                .ssh_key_passphrase
+                .as_ref()
+                .map(|s| s.is_empty())
+                .unwrap_or(true)
+            {
+                final_config.ssh_key_passphrase = saved.ssh_key_passphrase;
+            }
+            if final_config
+                .ssh_private_key
+                .as_ref()
+                .map(|s| s.is_empty())
+                .unwrap_or(true)
+            {
+                final_config.ssh_private_key = saved.ssh_private_key;
+            }
+            if final_config
+                .proxy_password
+                .as_ref()
+                .map(|s| s.is_empty())
+                .unwrap_or(true)
+            {
+                final_config.proxy_password = saved.proxy_password;
+            }
+            let saved_jumps = saved.ssh_jump_hosts.unwrap_or_default();
+            for jump in final_config.ssh_jump_hosts.iter_mut().flatten() {
+                if jump.password.as_ref().map(|s| s.is_empty()).unwrap_or(true) {
+                    jump.password = saved_jumps
+                        .iter()
+                        .find(|s| s.host == jump.host && s.user == jump.user)
+                        .and_then(|s| s.password.clone());
+                }
+            }
         }
     }
+    final_config
+}
+
+#[tauri::command]
+pub async fn test_connection(
+    config: ConnectionConfig,
+    connection_id: Option<String>,
+    state: State<'_, TauriState>,
+) -> Result<String, String> {
+    let final_config = with_saved_secrets(config, connection_id, &state);
 
     state
         .db
@@ -57,3 +118,39 @@ pub async fn test_connection(
         .map_err(|e| e.to_string())?;
     Ok("Connection successful".to_string())
 }
+
+#[tauri::command]
+pub async fn scan_ssh_host_keys(
+    config: ConnectionConfig,
+    connection_id: Option<String>,
+    state: State<'_, TauriState>,
+) -> Result<Vec<SshHostKey>, String> {
+    let final_config = with_saved_secrets(config, connection_id, &state);
+
+    let known_hosts = KnownHostsStore::new();
+
+    tokio::task::spawn_blocking(move || ssh::scan_host_keys(&final_config, &known_hosts))
+        .await
+        .map_err(|e| e.to_string())?
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn trust_ssh_host_key(
+    config: ConnectionConfig,
+    connection_id: Option<String>,
+    host: String,
+    port: u16,
+    fingerprint: String,
+    state: State<'_, TauriState>,
+) -> Result<(), String> {
+    let final_config = with_saved_secrets(config, connection_id, &state);
+
+    tokio::task::spawn_blocking(move || {
+        let known_hosts = KnownHostsStore::new();
+        ssh::trust_host_key(&final_config, &known_hosts, &host, port, &fingerprint)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
@@ -1,7 +1,9 @@
 use crate::commands::TauriState;
-use crate::models::{DataRequest, QueryResult, RowUpdate, UpdateResult};
+use crate::models::{DataRequest, QueryResult, RowBatch, RowUpdate, ScriptResult, UpdateResult};
 use std::fs::File;
 use std::io::Write;
+use std::sync::Arc;
+use tauri::ipc::Channel;
 use tauri::State;
 
 #[tauri::command]
@@ -9,9 +11,53 @@ pub async fn query(
     id: String,
     sql: String,
     query_id: Option<String>,
+    confirm_token: Option<String>,
     state: State<'_, TauriState>,
 ) -> Result<QueryResult, String> {
-    state.db.execute(id, sql, query_id).await.map_err(|e| e.to_string())
+    state
+        .db
+        .execute_confirmed(id, sql, query_id, confirm_token)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+// Sends the rows through `on_batch` as they are read and returns the result without
+// them. `max_rows` falls back to the row cap from the settings.
+#[tauri::command]
+pub async fn query_stream(
+    id: String,
+    sql: String,
+    query_id: Option<String>,
+    confirm_token: Option<String>,
+    max_rows: Option<usize>,
+    on_batch: Channel<RowBatch>,
+    state: State<'_, TauriState>,
+) -> Result<QueryResult, String> {
+    let max_rows = max_rows.unwrap_or_else(|| state.storage.get_settings().max_result_rows);
+    let sink = Arc::new(move |batch: RowBatch| {
+        // The window may be gone, the query still finishes
+        let _ = on_batch.send(batch);
+    });
+    state
+        .db
+        .execute_stream(id, sql, query_id, confirm_token, max_rows, sink)
+        .await
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn execute_script(
+    id: String,
+    sql: String,
+    stop_on_error: bool,
+    confirm_token: Option<String>,
+    state: State<'_, TauriState>,
+) -> Result<ScriptResult, String> {
+    state
+        .db
+        .execute_script(id, sql, stop_on_error, confirm_token)
+        .await
+        .map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -40,6 +86,11 @@ pub async fn export_query(
     if let Some(error) = result.error {
         return Err(error);
     }
+    if result.confirmation.is_some() {
+        return Err(
+            "Destructive statements can't be exported on a production connection".to_string(),
+        );
+    }
 
     let write_res = write_export_file(&result, &format, &path).map_err(|e| e.to_string());
     println!("Write export file result: {:?}", write_res);
@@ -53,20 +104,16 @@ fn write_export_file(result: &QueryResult, format: &str, path: &str) -> Result<(
         "csv" => {
             let mut wtr = csv::Writer::from_writer(file);
             // Write headers
-            wtr.write_record(&result.columns).map_err(|e| e.to_string())?;
+            wtr.write_record(result.columns.iter().map(|c| &c.name))
+                .map_err(|e| e.to_string())?;
             // Write rows
             for row in &result.rows {
-                let record: Vec<String> = result
-                    .columns
+                let record: Vec<String> = row
                     .iter()
-                    .map(|col| {
-                        row.get(col)
-                            .map(|v| match v {
-                                serde_json::Value::String(s) => s.clone(),
-                                serde_json::Value::Null => "".to_string(),
-                                _ => v.to_string(),
-                            })
-                            .unwrap_or_default()
+                    .map(|v| match v {
+                        serde_json::Value::String(s) => s.clone(),
+                        serde_json::Value::Null => "".to_string(),
+                        _ => v.to_string(),
                     })
                     .collect();
                 wtr.write_record(&record).map_err(|e| e.to_string())?;
@@ -74,26 +121,30 @@ fn write_export_file(result: &QueryResult, format: &str, path: &str) -> Result<(
             wtr.flush().map_err(|e| e.to_string())?;
         }
         "json" => {
-            let json = serde_json::to_string_pretty(&result.rows).map_err(|e| e.to_string())?;
+            // One object per row, keyed by column name
+            let names = result.unique_column_names();
+            let objects: Vec<serde_json::Map<String, serde_json::Value>> = result
+                .rows
+                .iter()
+                .map(|row| names.iter().cloned().zip(row.iter().cloned()).collect())
+                .collect();
+            let json = serde_json::to_string_pretty(&objects).map_err(|e| e.to_string())?;
             file.write_all(json.as_bytes())
                 .map_err(|e| e.to_string())?;
         }
         "sql" => {
             let table_name = "export_table"; // TODO: Maybe try to parse from SQL, but for now fixed
+            // A column list can't name the same column twice
+            let columns = result.unique_column_names();
             for row in &result.rows {
-                let columns: Vec<&String> = result.columns.iter().collect();
-                let values: Vec<String> = columns
+                let values: Vec<String> = row
                     .iter()
-                    .map(|col| {
-                        row.get(*col)
-                            .map(|v| match v {
-                                serde_json::Value::String(s) => format!("'{}'", s.replace("'", "''")),
-                                serde_json::Value::Null => "NULL".to_string(),
-                                serde_json::Value::Number(n) => n.to_string(),
-                                serde_json::Value::Bool(b) => b.to_string().to_uppercase(), // TRUE/FALSE
-                                _ => format!("'{}'", v.to_string().replace("'", "''")),
-                            })
-                            .unwrap_or_else(|| "NULL".to_string())
+                    .map(|v| match v {
+                        serde_json::Value::String(s) => format!("'{}'", s.replace("'", "''")),
+                        serde_json::Value::Null => "NULL".to_string(),
+                        serde_json::Value::Number(n) => n.to_string(),
+                        serde_json::Value::Bool(b) => b.to_string().to_uppercase(), // TRUE/FALSE
+                        _ => format!("'{}'", v.to_string().replace("'", "''")),
                     })
                     .collect();
 
@@ -119,24 +170,19 @@ fn write_export_file(result: &QueryResult, format: &str, path: &str) -> Result<(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::common::column_info;
     use std::fs;
     use serde_json::json;
-    use std::collections::HashMap;
 
     fn create_sample_result() -> QueryResult {
-        let mut row1 = HashMap::new();
-        row1.insert("id".to_string(), json!(1));
-        row1.insert("name".to_string(), json!("Alice"));
-        
-        let mut row2 = HashMap::new();
-        row2.insert("id".to_string(), json!(2));
-        row2.insert("name".to_string(), json!("Bob, O'Neil")); // Test comma handling (force quotes)
+        let row1 = vec![json!(1), json!("Alice")];
+        let row2 = vec![json!(2), json!("Bob, O'Neil")]; // Test comma handling (force quotes)
 
         QueryResult {
             rows: vec![row1, row2],
-            columns: vec!["id".to_string(), "name".to_string()],
-            error: None,
+            columns: vec![column_info("id", "int4"), column_info("name", "text")],
             duration: 0.1,
+            ..QueryResult::empty()
         }
     }
 
@@ -168,6 +214,27 @@ mod tests {
         let json: serde_json::Value = serde_json::from_str(&content).unwrap();
         assert!(json.is_array());
         assert_eq!(json.as_array().unwrap().len(), 2);
+        assert_eq!(json[1], json!({"id": 2, "name": "Bob, O'Neil"}));
+    }
+
+    #[test]
+    fn test_export_json_repeated_columns() {
+        let result = QueryResult {
+            rows: vec![vec![json!(1), json!(2), json!(3)]],
+            columns: ["id", "id", "id_2"]
+                .iter()
+                .map(|name| column_info(name, "int4"))
+                .collect(),
+            ..QueryResult::empty()
+        };
+        let path = "test_export_repeated.json";
+        write_export_file(&result, "json", path).unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(json[0], json!({"id": 1, "id_2": 2, "id_2_2": 3}));
     }
 
     #[test]
@@ -184,6 +251,28 @@ mod tests {
         assert!(content.contains("'Alice'"));
         assert!(content.contains("'Bob, O''Neil'")); // Escaped quote
     }
+
+    #[test]
+    fn test_export_sql_repeated_columns() {
+        let result = QueryResult {
+            rows: vec![vec![json!(1), json!(2)]],
+            columns: ["id", "id"]
+                .iter()
+                .map(|name| column_info(name, "int4"))
+                .collect(),
+            ..QueryResult::empty()
+        };
+        let path = "test_export_repeated.sql";
+        write_export_file(&result, "sql", path).unwrap();
+
+        let content = fs::read_to_string(path).unwrap();
+        fs::remove_file(path).unwrap();
+
+        assert_eq!(
+            content,
+            "INSERT INTO export_table (\"id\", \"id_2\") VALUES (1, 2);\n"
+        );
+    }
 }
 
 #[tauri::command]
@@ -1,5 +1,7 @@
 use crate::commands::TauriState;
-use crate::models::{AppSettings, AppStateData, ConnectionConfig, ConnectionSummary, HistoryItem};
+use crate::models::{
+    AppSettings, AppStateData, ConnectionConfig, ConnectionFolder, ConnectionSummary, HistoryItem,
+};
 use tauri::State;
 
 #[tauri::command]
@@ -20,6 +22,33 @@ pub async fn save_connection(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn parse_connection_string(dsn: String) -> Result<ConnectionConfig, String> {
+    ConnectionConfig::from_dsn(&dsn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_connection_string(
+    id: String,
+    state: State<'_, TauriState>,
+) -> Result<String, String> {
+    let connection = state
+        .storage
+        .get_connection(&id)
+        .ok_or_else(|| "Connection not found".to_string())?;
+    connection.to_dsn(true).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn import_client_connections(
+    state: State<'_, TauriState>,
+) -> Result<Vec<ConnectionSummary>, String> {
+    state
+        .storage
+        .import_client_configs()
+        .map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn delete_connection(id: String, state: State<'_, TauriState>) -> Result<(), String> {
     state
@@ -28,6 +57,50 @@ pub async fn delete_connection(id: String, state: State<'_, TauriState>) -> Resu
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+pub async fn move_connection(
+    id: String,
+    folder_id: Option<String>,
+    index: usize,
+    state: State<'_, TauriState>,
+) -> Result<(), String> {
+    state
+        .storage
+        .move_connection(&id, folder_id, index)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn get_folders(state: State<'_, TauriState>) -> Result<Vec<ConnectionFolder>, String> {
+    Ok(state.storage.get_folders())
+}
+
+#[tauri::command]
+pub async fn save_folder(
+    folder: ConnectionFolder,
+    state: State<'_, TauriState>,
+) -> Result<(), String> {
+    state.storage.save_folder(folder).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn move_folder(
+    id: String,
+    parent_id: Option<String>,
+    index: usize,
+    state: State<'_, TauriState>,
+) -> Result<(), String> {
+    state
+        .storage
+        .move_folder(&id, parent_id, index)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub async fn delete_folder(id: String, state: State<'_, TauriState>) -> Result<(), String> {
+    state.storage.delete_folder(&id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 pub async fn get_settings(state: State<'_, TauriState>) -> Result<AppSettings, String> {
     Ok(state.storage.get_settings())
@@ -1,23 +1,31 @@
+use crate::db::common::{
+    clickhouse_nullable, clickhouse_settings, column_info, statement_timeout, BatchSink, RowStream,
+};
+use crate::db::proxy::ProxySettings;
 use crate::db::traits::DatabaseService;
 use crate::error::{DbError, Result};
 use crate::models::{
-    ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
-    UpdateResult,
+    ColumnInfo, ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
+    SslMode, UpdateResult,
 };
 use async_trait::async_trait;
-use reqwest::{Client, Url};
+use reqwest::header::HeaderMap;
+use reqwest::{Certificate, Client, Identity, Proxy, RequestBuilder, Url};
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashMap;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 #[derive(Debug, Deserialize)]
 #[allow(dead_code)]
 struct ClickHouseResponse {
     meta: Option<Vec<ClickHouseMeta>>,
-    data: Option<Vec<HashMap<String, Value>>>,
+    data: Option<Vec<Vec<Value>>>,
     rows: Option<u64>,
     statistics: Option<ClickHouseStatistics>,
+    // From the X-ClickHouse-Summary header rather than the body
+    #[serde(skip)]
+    written_rows: Option<u64>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -49,27 +57,138 @@ impl ClickhouseService {
         }
     }
 
+    // The HTTP interface can't negotiate TLS, so only the strict modes switch to HTTPS.
+    fn use_https(config: &ConnectionConfig) -> bool {
+        matches!(
+            config.ssl_mode,
+            Some(SslMode::Require) | Some(SslMode::VerifyCa) | Some(SslMode::VerifyFull)
+        )
+    }
+
+    fn build_client(config: &ConnectionConfig) -> Result<Client> {
+        let mut builder = Client::builder();
+        if let Some(timeout) = config.connect_timeout {
+            builder = builder.connect_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(timeout) = config.http_timeout.filter(|t| *t > 0) {
+            builder = builder.timeout(Duration::from_secs(timeout));
+        }
+        // Every query is its own HTTP request, so the pool limit caps idle keep-alive
+        // connections rather than concurrency
+        if let Some(max) = config.max_connections.filter(|n| *n > 0) {
+            builder = builder.pool_max_idle_per_host(max as usize);
+        }
+        if let Some(timeout) = config.idle_timeout.filter(|t| *t > 0) {
+            builder = builder.pool_idle_timeout(Duration::from_secs(timeout));
+        }
+        if let Some(name) = config.application_name.as_deref().filter(|n| !n.is_empty()) {
+            // Shows up as http_user_agent in system.query_log
+            builder = builder.user_agent(name);
+        }
+        if let Some(proxy) = ProxySettings::from_config(config)? {
+            let proxy = Proxy::all(proxy.url()?).map_err(|e| DbError::Proxy(e.to_string()))?;
+            builder = builder.proxy(proxy);
+        }
+
+        if Self::use_https(config) {
+            if let Some(ca) = config.ssl_ca_path.as_deref().filter(|p| !p.is_empty()) {
+                let pem = std::fs::read(ca)?;
+                let cert = Certificate::from_pem(&pem)
+                    .map_err(|e| DbError::Config(format!("Invalid CA certificate: {}", e)))?;
+                builder = builder.add_root_certificate(cert);
+            }
+
+            let cert_path = config.ssl_cert_path.as_deref().filter(|p| !p.is_empty());
+            let key_path = config.ssl_key_path.as_deref().filter(|p| !p.is_empty());
+            match (cert_path, key_path) {
+                (Some(cert), Some(key)) => {
+                    let identity = Identity::from_pkcs8_pem(&std::fs::read(cert)?, &std::fs::read(key)?)
+                        .map_err(|e| DbError::Config(format!("Invalid client certificate: {}", e)))?;
+                    builder = builder.identity(identity);
+                }
+                (None, None) => {}
+                _ => {
+                    return Err(DbError::Config(
+                        "Client certificate and key must be provided together".to_string(),
+                    ))
+                }
+            }
+
+            // Same meaning as libpq: require only encrypts, verify-ca skips the hostname
+            match config.ssl_mode {
+                Some(SslMode::Require) => {
+                    builder = builder
+                        .danger_accept_invalid_certs(true)
+                        .danger_accept_invalid_hostnames(true);
+                }
+                Some(SslMode::VerifyCa) => {
+                    builder = builder.danger_accept_invalid_hostnames(true);
+                }
+                _ => {}
+            }
+            if config.ssl_accept_invalid_certs.unwrap_or(false) {
+                builder = builder.danger_accept_invalid_certs(true);
+            }
+        }
+
+        builder.build().map_err(|e| DbError::Config(e.to_string()))
+    }
+
     fn get_url(&self, extra_params: &[(&str, &str)]) -> Result<Url> {
+        self.query_url(&[], extra_params)
+    }
+
+    // `query_settings` go in with the connection's settings, ahead of `readonly`, and
+    // replace the default format when they name one
+    fn query_url(
+        &self,
+        query_settings: &[(&str, &str)],
+        extra_params: &[(&str, &str)],
+    ) -> Result<Url> {
         let config = self.config.as_ref().ok_or(DbError::NotConnected)?;
-        let host = if config.host == "localhost" {
+        let https = Self::use_https(config);
+        // Keep the original host name for HTTPS so certificate verification matches it
+        let host = if config.host == "localhost" && !https {
             "127.0.0.1"
         } else {
             &config.host
         };
-        let port = if config.port == 0 { 8123 } else { config.port };
+        let (scheme, default_port) = if https { ("https", 8443) } else { ("http", 8123) };
+        let port = if config.port == 0 { default_port } else { config.port };
         
-        let url_str = format!("http://{}:{}/", host, port);
+        let url_str = format!("{}://{}:{}/", scheme, host, port);
         
         let mut url = Url::parse(&url_str).map_err(|e| DbError::Config(e.to_string()))?;
+        let settings = clickhouse_settings(config)?;
         
         {
             let mut pairs = url.query_pairs_mut();
             if !config.database.is_empty() {
                 pairs.append_pair("database", &config.database);
             }
-            pairs.append_pair("default_format", "JSON");
+            // Rows as arrays, so repeated column names don't overwrite each other
+            let format = query_settings
+                .iter()
+                .find(|(name, _)| *name == "default_format")
+                .map_or("JSONCompact", |(_, value)| *value);
+            pairs.append_pair("default_format", format);
             pairs.append_pair("output_format_json_quote_64bit_integers", "1");
             pairs.append_pair("output_format_json_quote_denormals", "1");
+            if let Some(timeout) = statement_timeout(config) {
+                pairs.append_pair("max_execution_time", &timeout.as_secs().to_string());
+            }
+            for (name, value) in &settings {
+                pairs.append_pair(name, value);
+            }
+            for (name, value) in query_settings {
+                if *name != "default_format" {
+                    pairs.append_pair(name, value);
+                }
+            }
+            // Last among the settings, later changes would be refused under it
+            if config.read_only.unwrap_or(false) {
+                pairs.append_pair("readonly", "1");
+            }
             
             for (k, v) in extra_params {
                 pairs.append_pair(k, v);
@@ -79,33 +198,39 @@ impl ClickhouseService {
         Ok(url)
     }
 
-    async fn send_query(&self, sql: &str, query_id: Option<&str>) -> Result<ClickHouseResponse> {
+    fn request(&self, url: Url, sql: &str) -> Result<RequestBuilder> {
         let client = self.client.as_ref().ok_or(DbError::NotConnected)?;
         let config = self.config.as_ref().ok_or(DbError::NotConnected)?;
-        
-        let mut params = Vec::new();
-        if let Some(qid) = query_id {
-            params.push(("query_id", qid));
-        }
 
-        let url = self.get_url(&params)?;
-        
         let mut req = client.post(url).body(sql.to_string());
-
         if !config.user.is_empty() {
             req = req.header("X-ClickHouse-User", &config.user);
         }
         if let Some(pass) = &config.password {
-             req = req.header("X-ClickHouse-Key", pass);
+            req = req.header("X-ClickHouse-Key", pass);
         }
+        Ok(req)
+    }
 
-        let res = req.send().await.map_err(|e| DbError::Connection(e.to_string()))?;
+    async fn send_query(&self, sql: &str, query_id: Option<&str>) -> Result<ClickHouseResponse> {
+        let mut params = Vec::new();
+        if let Some(qid) = query_id {
+            params.push(("query_id", qid));
+        }
+
+        let url = self.get_url(&params)?;
+        let res = self
+            .request(url, sql)?
+            .send()
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?;
         
         if !res.status().is_success() {
              let text = res.text().await.unwrap_or_default();
              return Err(DbError::Query(text));
         }
 
+        let written_rows = written_rows(res.headers());
         let text = res.text().await.map_err(|e| DbError::Connection(e.to_string()))?;
         
         if text.trim().is_empty() {
@@ -113,7 +238,8 @@ impl ClickhouseService {
                  meta: None,
                  data: None,
                  rows: Some(0),
-                 statistics: None
+                 statistics: None,
+                 written_rows,
              });
         }
 
@@ -121,7 +247,7 @@ impl ClickhouseService {
         // (if user overrode FORMAT). In that case we can't do much for grid, 
         // but we should avoid crashing.
         match serde_json::from_str::<ClickHouseResponse>(&text) {
-            Ok(r) => Ok(r),
+            Ok(r) => Ok(ClickHouseResponse { written_rows, ..r }),
             Err(_) => {
                 // If it's valid JSON but not our structure (e.g. strict format) or plain text
                 // Return a dummy response with raw text?
@@ -130,13 +256,112 @@ impl ClickhouseService {
             }
         }
     }
+
+    // Reads the output one row per line as it arrives: column names, then types, then
+    // the rows. Closing the response early makes the server cancel the query.
+    async fn stream_query(
+        &self,
+        sql: &str,
+        query_id: Option<&str>,
+        rows: &mut RowStream,
+    ) -> Result<(Vec<ColumnInfo>, Option<u64>)> {
+        let mut params = Vec::new();
+        if let Some(qid) = query_id {
+            params.push(("query_id", qid));
+        }
+        let query_settings = [
+            ("default_format", "JSONCompactEachRowWithNamesAndTypes"),
+            ("cancel_http_readonly_queries_on_client_close", "1"),
+        ];
+
+        let url = self.query_url(&query_settings, &params)?;
+        let mut res = self
+            .request(url, sql)?
+            .send()
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?;
+        if !res.status().is_success() {
+            let text = res.text().await.unwrap_or_default();
+            return Err(DbError::Query(text));
+        }
+        let written_rows = written_rows(res.headers());
+
+        let mut header: Vec<Vec<String>> = Vec::new();
+        let mut buffer = Vec::new();
+        while let Some(chunk) = res
+            .chunk()
+            .await
+            .map_err(|e| DbError::Connection(e.to_string()))?
+        {
+            buffer.extend_from_slice(&chunk);
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let Ok(values) = serde_json::from_slice::<Vec<Value>>(&buffer[..end]) else {
+                    // An exception after the output has started comes as plain text in
+                    // place of the next row
+                    while let Ok(Some(chunk)) = res.chunk().await {
+                        buffer.extend_from_slice(&chunk);
+                    }
+                    break;
+                };
+                buffer.drain(..=end);
+                if header.len() < 2 {
+                    header.push(
+                        values
+                            .iter()
+                            .map(|v| v.as_str().unwrap_or_default().to_string())
+                            .collect(),
+                    );
+                    if header.len() == 2 {
+                        rows.set_columns(columns_from_header(&header));
+                    }
+                } else if !rows.push(values) {
+                    return Ok((columns_from_header(&header), written_rows));
+                }
+            }
+        }
+
+        let rest = String::from_utf8_lossy(&buffer);
+        if !rest.trim().is_empty() {
+            return Err(DbError::Query(rest.trim().to_string()));
+        }
+        Ok((columns_from_header(&header), written_rows))
+    }
+}
+
+// Column names and types from the first two lines of a `...WithNamesAndTypes` output
+fn columns_from_header(header: &[Vec<String>]) -> Vec<ColumnInfo> {
+    let [names, types] = header else {
+        return Vec::new();
+    };
+    names
+        .iter()
+        .zip(types)
+        .map(|(name, type_)| ColumnInfo {
+            nullable: Some(clickhouse_nullable(type_)),
+            ..column_info(name, type_)
+        })
+        .collect()
+}
+
+// The summary header carries the query's counters as a JSON object of quoted numbers,
+// e.g. `{"read_rows":"0","written_rows":"3",...}`
+fn written_rows(headers: &HeaderMap) -> Option<u64> {
+    let summary = headers.get("X-ClickHouse-Summary")?;
+    let summary: Value = serde_json::from_slice(summary.as_bytes()).ok()?;
+    summary["written_rows"].as_str()?.parse().ok()
+}
+
+impl Default for ClickhouseService {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 #[async_trait]
 impl DatabaseService for ClickhouseService {
     async fn connect(&mut self, config: &ConnectionConfig) -> Result<String> {
+        self.client = Some(Self::build_client(config)?);
         self.config = Some(config.clone());
-        self.client = Some(Client::new());
         
         // Test connection
         self.send_query("SELECT 1", None).await?;
@@ -157,15 +382,19 @@ impl DatabaseService for ClickhouseService {
                 let duration = start.elapsed().as_secs_f64() * 1000.0;
                 let columns = response.meta.unwrap_or_default()
                     .into_iter()
-                    .map(|m| m.name)
+                    .map(|m| ColumnInfo {
+                        nullable: Some(clickhouse_nullable(&m.type_)),
+                        ..column_info(&m.name, &m.type_)
+                    })
                     .collect();
                 let rows = response.data.unwrap_or_default();
                 
                 Ok(QueryResult {
                     rows,
                     columns,
-                    error: None,
                     duration,
+                    rows_affected: response.written_rows,
+                    ..QueryResult::empty()
                 })
             }
             Err(e) => {
@@ -175,6 +404,31 @@ impl DatabaseService for ClickhouseService {
         }
     }
 
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let mut rows = RowStream::new(sink, max_rows);
+        let streamed = self.stream_query(sql, query_id.as_deref(), &mut rows).await;
+        let truncated = rows.finish();
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+        match streamed {
+            Ok((columns, written_rows)) => Ok(QueryResult {
+                columns,
+                duration,
+                rows_affected: written_rows,
+                truncated,
+                ..QueryResult::empty()
+            }),
+            Err(e) => Ok(QueryResult::with_error(e.to_string(), duration)),
+        }
+    }
+
     async fn cancel_query(&self, query_id: String) -> Result<()> {
         // KILL QUERY WHERE query_id = '...'
         let kill_sql = format!("KILL QUERY WHERE query_id = '{}'", query_id);
@@ -191,12 +445,12 @@ impl DatabaseService for ClickhouseService {
          };
          
          let result = self.execute(&sql, None).await?;
-         if let Some(_) = result.error {
+         if result.error.is_some() {
              return Err(DbError::Query("Failed to get tables".to_string()));
          }
          
          let tables = result.rows.iter()
-             .filter_map(|r| r.get("name").or(r.get("name"))) // Column name is usually "name" for SHOW TABLES
+             .filter_map(|r| r.first()) // SHOW TABLES returns a single "name" column
              .filter_map(|v| v.as_str().map(|s| s.to_string()))
              .collect();
              
@@ -205,12 +459,12 @@ impl DatabaseService for ClickhouseService {
 
     async fn get_databases(&self) -> Result<Vec<String>> {
         let result = self.execute("SHOW DATABASES", None).await?;
-         if let Some(_) = result.error {
+         if result.error.is_some() {
              return Err(DbError::Query("Failed to get databases".to_string()));
          }
          
          let dbs = result.rows.iter()
-             .filter_map(|r| r.get("name"))
+             .filter_map(|r| r.first())
              .filter_map(|v| v.as_str().map(|s| s.to_string()))
              .collect();
              
@@ -226,13 +480,13 @@ impl DatabaseService for ClickhouseService {
         );
         
         let result = self.execute(&sql, None).await?;
-         if let Some(_) = result.error {
+         if result.error.is_some() {
              return Err(DbError::Query("Failed to get schema".to_string()));
          }
          
         let mut schema: DbSchema = HashMap::new();
         for row in result.rows {
-            if let (Some(table_val), Some(col_val)) = (row.get("table"), row.get("name")) {
+            if let (Some(table_val), Some(col_val)) = (row.first(), row.get(1)) {
                 if let (Some(table), Some(col)) = (table_val.as_str(), col_val.as_str()) {
                     schema.entry(table.to_string()).or_default().push(col.to_string());
                 }
@@ -286,7 +540,7 @@ impl DatabaseService for ClickhouseService {
         
         let result = self.execute(&sql, None).await?;
         let keys = result.rows.iter()
-             .filter_map(|r| r.get("name"))
+             .filter_map(|r| r.first())
              .filter_map(|v| v.as_str().map(|s| s.to_string()))
              .collect();
              
@@ -305,14 +559,14 @@ impl DatabaseService for ClickhouseService {
         // Run parallel queries or seq
         let version_res = self.execute("SELECT version() as v", None).await?;
         let version = version_res.rows.first()
-            .and_then(|r| r.get("v"))
+            .and_then(|r| r.first())
             .and_then(|v| v.as_str())
             .unwrap_or("unknown")
             .to_string();
             
         let uptime_res = self.execute("SELECT uptime() as u", None).await?;
         let uptime: i64 = uptime_res.rows.first()
-            .and_then(|r| r.get("u"))
+            .and_then(|r| r.first())
             .and_then(|v| v.as_u64())
             .unwrap_or(0) as i64;
             
@@ -320,14 +574,14 @@ impl DatabaseService for ClickhouseService {
         // SELECT sum(bytes) FROM system.parts
         let size_res = self.execute("SELECT formatReadableSize(sum(bytes)) as s FROM system.parts", None).await?;
         let db_size = size_res.rows.first()
-            .and_then(|r| r.get("s"))
+            .and_then(|r| r.first())
             .and_then(|v| v.as_str())
             .unwrap_or("0 B")
             .to_string();
             
         let table_count_res = self.execute("SELECT count() as c FROM system.tables WHERE database != 'system'", None).await?;
         let table_count = table_count_res.rows.first()
-            .and_then(|r| r.get("c"))
+            .and_then(|r| r.first())
             .and_then(|v| v.as_u64())
             .unwrap_or(0) as i32;
 
@@ -340,7 +594,147 @@ impl DatabaseService for ClickhouseService {
             indexes_size: "N/A".to_string(),
             table_count,
             cache_hit_ratio: 0.0, 
-            top_queries: vec![]
+            top_queries: vec![],
+            extra: HashMap::new(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DatabaseDriver;
+
+    fn service_with(config: ConnectionConfig) -> ClickhouseService {
+        ClickhouseService {
+            client: None,
+            config: Some(config),
+        }
+    }
+
+    #[test]
+    fn test_get_url_plain_http() {
+        let service = service_with(ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            host: "localhost".to_string(),
+            port: 0,
+            ..Default::default()
+        });
+
+        let url = service.get_url(&[]).unwrap();
+        assert_eq!(url.scheme(), "http");
+        assert_eq!(url.host_str(), Some("127.0.0.1"));
+        assert_eq!(url.port(), Some(8123));
+    }
+
+    #[test]
+    fn test_get_url_https() {
+        let service = service_with(ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            host: "localhost".to_string(),
+            port: 0,
+            ssl_mode: Some(SslMode::VerifyFull),
+            ..Default::default()
+        });
+
+        let url = service.get_url(&[]).unwrap();
+        assert_eq!(url.scheme(), "https");
+        assert_eq!(url.host_str(), Some("localhost"));
+        assert_eq!(url.port(), Some(8443));
+    }
+
+    #[test]
+    fn test_get_url_statement_timeout() {
+        let service = service_with(ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            host: "localhost".to_string(),
+            port: 0,
+            statement_timeout: Some(30),
+            ..Default::default()
+        });
+
+        let url = service.get_url(&[]).unwrap();
+        assert!(url
+            .query_pairs()
+            .any(|(k, v)| k == "max_execution_time" && v == "30"));
+    }
+
+    #[test]
+    fn test_get_url_read_only() {
+        let service = service_with(ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            host: "localhost".to_string(),
+            port: 0,
+            read_only: Some(true),
+            ..Default::default()
+        });
+
+        let url = service.get_url(&[("query_id", "q1")]).unwrap();
+        let pairs: Vec<_> = url.query_pairs().collect();
+        assert!(pairs.iter().any(|(k, v)| k == "readonly" && v == "1"));
+        assert_eq!(pairs.last().unwrap().0, "query_id");
+    }
+
+    #[test]
+    fn test_get_url_session_settings() {
+        let service = service_with(ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            host: "localhost".to_string(),
+            port: 0,
+            read_only: Some(true),
+            init_script: Some("SET session_timezone = 'Europe/Berlin'".to_string()),
+            ..Default::default()
+        });
+
+        let url = service.get_url(&[]).unwrap();
+        let names: Vec<_> = url.query_pairs().map(|(k, _)| k.into_owned()).collect();
+        assert!(url
+            .query_pairs()
+            .any(|(k, v)| k == "session_timezone" && v == "Europe/Berlin"));
+        assert_eq!(names.last().unwrap(), "readonly");
+    }
+
+    #[test]
+    fn test_query_url_settings() {
+        let service = service_with(ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            host: "localhost".to_string(),
+            port: 0,
+            read_only: Some(true),
+            ..Default::default()
+        });
+
+        let url = service
+            .query_url(
+                &[
+                    ("default_format", "JSONCompactEachRowWithNamesAndTypes"),
+                    ("cancel_http_readonly_queries_on_client_close", "1"),
+                ],
+                &[("query_id", "q1")],
+            )
+            .unwrap();
+        let names: Vec<_> = url.query_pairs().map(|(k, _)| k.into_owned()).collect();
+        let formats: Vec<_> = url
+            .query_pairs()
+            .filter(|(k, _)| k == "default_format")
+            .map(|(_, v)| v.into_owned())
+            .collect();
+        assert_eq!(formats, ["JSONCompactEachRowWithNamesAndTypes"]);
+        assert_eq!(
+            names[names.len() - 3..],
+            ["cancel_http_readonly_queries_on_client_close", "readonly", "query_id"]
+        );
+    }
+
+    #[test]
+    fn test_build_client_requires_cert_and_key() {
+        let config = ConnectionConfig {
+            driver: DatabaseDriver::Clickhouse,
+            ssl_mode: Some(SslMode::Require),
+            ssl_cert_path: Some("/nonexistent/client.pem".to_string()),
+            ..Default::default()
+        };
+
+        assert!(ClickhouseService::build_client(&config).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::models::{ColumnInfo, LogicalType};
+
+// Column metadata from a name and the database's type name. Drivers fill in
+// nullability and the source column when they know them.
+pub fn column_info(name: &str, type_name: &str) -> ColumnInfo {
+    ColumnInfo {
+        name: name.to_string(),
+        type_name: type_name.to_string(),
+        logical_type: logical_type(type_name),
+        nullable: None,
+        source_table: None,
+        source_column: None,
+    }
+}
+
+// Maps type names of every supported database onto one set of logical types:
+// Postgres (`int4`, `_text`, `timestamptz`), MySQL (`INT UNSIGNED`, `LONGBLOB`),
+// SQLite declared types, ClickHouse (`Nullable(Decimal(18, 2))`) and the Arrow
+// names DuckDB reports (`Utf8`, `Timestamp(Microsecond, None)`).
+pub fn logical_type(type_name: &str) -> LogicalType {
+    let lower = type_name.trim().to_ascii_lowercase();
+    let name = strip_clickhouse_wrappers(&lower);
+
+    if name.ends_with("[]")
+        || name.starts_with('_')
+        || name.starts_with("array(")
+        || name.starts_with("list(")
+        || name.starts_with("largelist(")
+    {
+        return LogicalType::Array;
+    }
+
+    // Parameters don't change the kind of type: `varchar(255)`, `decimal(10, 2)`
+    let base = name.split('(').next().unwrap_or_default();
+    match base.split_whitespace().next().unwrap_or_default() {
+        "bool" | "boolean" => LogicalType::Boolean,
+        "int" | "int1" | "int2" | "int4" | "int8" | "int16" | "int32" | "int64" | "int128"
+        | "int256" | "integer" | "smallint" | "tinyint" | "mediumint" | "bigint" | "hugeint"
+        | "uint8" | "uint16" | "uint32" | "uint64" | "uint128" | "uint256" | "utinyint"
+        | "usmallint" | "uinteger" | "ubigint" | "uhugeint" | "oid" | "year" => {
+            LogicalType::Integer
+        }
+        "numeric" | "decimal" | "decimal32" | "decimal64" | "decimal128" | "decimal256"
+        | "money" => LogicalType::Decimal,
+        "float" | "float4" | "float8" | "float16" | "float32" | "float64" | "double" | "real" => {
+            LogicalType::Float
+        }
+        "text" | "varchar" | "char" | "bpchar" | "character" | "name" | "citext" | "string"
+        | "fixedstring" | "tinytext" | "mediumtext" | "longtext" | "utf8" | "largeutf8"
+        | "enum" | "enum8" | "enum16" | "set" | "xml" | "nchar" | "nvarchar" | "clob" => {
+            LogicalType::Text
+        }
+        "json" | "jsonb" | "object" => LogicalType::Json,
+        "uuid" => LogicalType::Uuid,
+        "date" | "date32" => LogicalType::Date,
+        "time" | "timetz" | "time32" | "time64" => LogicalType::Time,
+        "timestamp" | "timestamptz" | "datetime" | "datetime64" | "timestamp_s"
+        | "timestamp_ms" | "timestamp_ns" => LogicalType::Timestamp,
+        "bytea" | "blob" | "binary" | "varbinary" | "tinyblob" | "mediumblob" | "longblob"
+        | "largebinary" | "fixedsizebinary" => LogicalType::Binary,
+        _ => LogicalType::Other,
+    }
+}
+
+// ClickHouse marks NULL-able columns in the type itself
+pub fn clickhouse_nullable(type_name: &str) -> bool {
+    type_name.starts_with("Nullable(") || type_name.starts_with("LowCardinality(Nullable(")
+}
+
+fn strip_clickhouse_wrappers(name: &str) -> &str {
+    let mut name = name;
+    while let Some(inner) = ["nullable(", "lowcardinality("]
+        .iter()
+        .find_map(|wrapper| name.strip_prefix(wrapper)?.strip_suffix(')'))
+    {
+        name = inner;
+    }
+    name
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logical_types_across_dialects() {
+        let cases = [
+            ("int4", LogicalType::Integer),
+            ("INT UNSIGNED", LogicalType::Integer),
+            ("Nullable(UInt64)", LogicalType::Integer),
+            ("numeric", LogicalType::Decimal),
+            ("Decimal128(10, 2)", LogicalType::Decimal),
+            ("double precision", LogicalType::Float),
+            ("character varying(255)", LogicalType::Text),
+            ("LowCardinality(Nullable(String))", LogicalType::Text),
+            ("Utf8", LogicalType::Text),
+            ("jsonb", LogicalType::Json),
+            ("timestamp with time zone", LogicalType::Timestamp),
+            ("DateTime64(3, 'UTC')", LogicalType::Timestamp),
+            ("Timestamp(Microsecond, None)", LogicalType::Timestamp),
+            ("DATE", LogicalType::Date),
+            ("LONGBLOB", LogicalType::Binary),
+            ("_int4", LogicalType::Array),
+            ("Array(String)", LogicalType::Array),
+            ("interval", LogicalType::Other),
+        ];
+        for (type_name, expected) in cases {
+            assert_eq!(logical_type(type_name), expected, "{}", type_name);
+        }
+    }
+
+    #[test]
+    fn test_clickhouse_nullable() {
+        assert!(clickhouse_nullable("Nullable(Int32)"));
+        assert!(clickhouse_nullable("LowCardinality(Nullable(String))"));
+        assert!(!clickhouse_nullable("LowCardinality(String)"));
+    }
+}
@@ -0,0 +1,135 @@
+use super::lexer::{statement_verb, tokenize, top_level_words, TokenKind};
+use crate::models::QueryResult;
+
+// Words that can stand between CREATE/DROP/ALTER and the kind of object
+const MODIFIERS: &[&str] = &[
+    "OR",
+    "REPLACE",
+    "UNIQUE",
+    "TEMP",
+    "TEMPORARY",
+    "UNLOGGED",
+    "GLOBAL",
+    "LOCAL",
+];
+
+// Postgres reports what a statement did in its command tag, which sqlx doesn't pass on.
+// So the tag is rebuilt from the last statement of `sql` the way Postgres would word it:
+// the verb and row count for queries and DML (`SELECT 3`, `INSERT 0 5`, `UPDATE 42`),
+// `SELECT n` for CREATE TABLE AS and SELECT INTO, the verb and kind of object for other
+// DDL (`CREATE TABLE`), and the verb alone otherwise. It is only a reconstruction and
+// can differ from what the server sent. Failed statements get no tag. `row_count` is
+// the number of rows returned, which for streamed queries isn't in the result.
+pub fn synthetic_command_tag(sql: &str, result: &QueryResult, row_count: usize) -> Option<String> {
+    if result.error.is_some() {
+        return None;
+    }
+
+    let tokens = tokenize(sql);
+    let statement = tokens
+        .split(|t| t.kind == TokenKind::Punct(';'))
+        .rfind(|s| !s.is_empty())?;
+    let top_level = top_level_words(sql, statement);
+    let has_word = |word: &str| top_level.iter().any(|w| w == word);
+    let mut words = top_level.iter().map(String::as_str);
+    let verb = statement_verb(&mut words)?;
+    let written = result.rows_affected.unwrap_or(0);
+
+    let tag = match verb {
+        "SELECT" if has_word("INTO") => format!("SELECT {}", written),
+        "SELECT" | "VALUES" | "TABLE" => format!("SELECT {}", row_count),
+        // The 0 is the OID Postgres still reports for the inserted row
+        "INSERT" => match result.rows_affected {
+            Some(count) => format!("INSERT 0 {}", count),
+            None => verb.to_string(),
+        },
+        "UPDATE" | "DELETE" | "MERGE" | "COPY" => match result.rows_affected {
+            Some(count) => format!("{} {}", verb, count),
+            None => verb.to_string(),
+        },
+        "CREATE" | "DROP" | "ALTER" => {
+            let mut tag = verb.to_string();
+            if let Some(object) = words.find(|w| !MODIFIERS.contains(w)) {
+                // `CREATE TABLE ... AS SELECT` reports the rows it wrote
+                if verb == "CREATE" && object == "TABLE" && has_word("AS") {
+                    return Some(format!("SELECT {}", written));
+                }
+                tag = format!("{} {}", tag, object);
+                // `CREATE MATERIALIZED VIEW`, `DROP FOREIGN TABLE`
+                if matches!(object, "MATERIALIZED" | "FOREIGN") {
+                    if let Some(kind) = words.next() {
+                        tag = format!("{} {}", tag, kind);
+                    }
+                }
+            }
+            tag
+        }
+        verb => verb.to_string(),
+    };
+    Some(tag)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(sql: &str, rows_affected: Option<u64>, rows: usize) -> Option<String> {
+        let result = QueryResult {
+            rows_affected,
+            ..QueryResult::empty()
+        };
+        synthetic_command_tag(sql, &result, rows)
+    }
+
+    #[test]
+    fn test_command_tags() {
+        let cases = [
+            ("select * from t", None, 3, "SELECT 3"),
+            (
+                "UPDATE t SET a = 1 WHERE b IN (SELECT b FROM c)",
+                Some(42),
+                0,
+                "UPDATE 42",
+            ),
+            ("WITH x AS (SELECT 1) DELETE FROM t", Some(2), 0, "DELETE 2"),
+            ("INSERT INTO t VALUES (1)", None, 0, "INSERT"),
+            ("INSERT INTO t SELECT * FROM s", Some(5), 0, "INSERT 0 5"),
+            ("CREATE TABLE t AS SELECT * FROM s", Some(5), 0, "SELECT 5"),
+            ("SELECT * INTO t FROM s", Some(5), 0, "SELECT 5"),
+            (
+                "CREATE OR REPLACE TEMP VIEW v AS SELECT 1",
+                Some(0),
+                0,
+                "CREATE VIEW",
+            ),
+            ("CREATE UNIQUE INDEX i ON t (a)", Some(0), 0, "CREATE INDEX"),
+            (
+                "drop materialized view if exists v",
+                Some(0),
+                0,
+                "DROP MATERIALIZED VIEW",
+            ),
+            (
+                "-- set up\nSET search_path = app; BEGIN;",
+                Some(0),
+                0,
+                "BEGIN",
+            ),
+        ];
+        for (sql, rows_affected, rows, expected) in cases {
+            assert_eq!(
+                tag(sql, rows_affected, rows).as_deref(),
+                Some(expected),
+                "{}",
+                sql
+            );
+        }
+    }
+
+    #[test]
+    fn test_no_tag_for_errors() {
+        let result = QueryResult::with_error("syntax error".to_string(), 0.0);
+        assert_eq!(synthetic_command_tag("SELEC 1", &result, 0), None);
+        assert_eq!(tag("-- nothing", None, 0), None);
+    }
+}
@@ -0,0 +1,143 @@
+use super::lexer::{statement_verb, tokenize_for, top_level_words, Dialect, Token, TokenKind};
+use super::splitter::split_statements;
+
+// Reasons a script needs confirmation before it runs against a protected database:
+// one entry per kind of destructive statement it contains, in order of appearance.
+// The script is split and lexed for the connection's dialect, the same way it will
+// be run.
+pub fn destructive_reasons(sql: &str, dialect: Dialect) -> Vec<String> {
+    let mut reasons: Vec<String> = Vec::new();
+    for statement in split_statements(sql, dialect) {
+        let tokens = tokenize_for(statement, dialect);
+        collect_reasons(statement, &tokens, &mut reasons);
+    }
+    reasons
+}
+
+fn collect_reasons(sql: &str, tokens: &[Token], reasons: &mut Vec<String>) {
+    // Data-modifying CTEs run along with the main statement:
+    // `WITH gone AS (DELETE FROM t RETURNING *) SELECT * FROM gone`
+    for body in modifying_subqueries(sql, tokens) {
+        collect_reasons(sql, body, reasons);
+    }
+    if let Some(reason) = classify(sql, tokens) {
+        if !reasons.iter().any(|r| r == reason) {
+            reasons.push(reason.to_string());
+        }
+    }
+}
+
+fn classify(sql: &str, tokens: &[Token]) -> Option<&'static str> {
+    let top_level = top_level_words(sql, tokens);
+    let mut words = top_level.iter().map(String::as_str);
+    let verb = statement_verb(&mut words)?;
+
+    match verb {
+        "DROP" => Some("DROP"),
+        "TRUNCATE" => Some("TRUNCATE"),
+        "ALTER" => Some("ALTER"),
+        "DELETE" if !words.any(|w| w == "WHERE") => Some("DELETE without WHERE"),
+        "UPDATE" if !words.any(|w| w == "WHERE") => Some("UPDATE without WHERE"),
+        _ => None,
+    }
+}
+
+// Top-level parenthesized bodies that start with DELETE, UPDATE or a nested WITH
+fn modifying_subqueries<'t>(sql: &str, tokens: &'t [Token]) -> Vec<&'t [Token]> {
+    let mut bodies = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::Punct('(') => {
+                if depth == 0 {
+                    start = i + 1;
+                }
+                depth += 1;
+            }
+            TokenKind::Punct(')') if depth > 0 => {
+                depth -= 1;
+                let body = &tokens[start..i];
+                let modifies = body.first().is_some_and(|t| {
+                    ["WITH", "DELETE", "UPDATE"]
+                        .iter()
+                        .any(|verb| t.is_word(sql, verb))
+                });
+                if depth == 0 && modifies {
+                    bodies.push(body);
+                }
+            }
+            _ => {}
+        }
+    }
+    bodies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reasons(sql: &str) -> Vec<String> {
+        destructive_reasons(sql, Dialect::Generic)
+    }
+
+    #[test]
+    fn test_safe_statements() {
+        for sql in [
+            "SELECT * FROM users",
+            "DELETE FROM users WHERE id = 1",
+            "update users set name = 'x' where id in (select id from old)",
+            "INSERT INTO log VALUES ('DROP TABLE users')",
+            "-- DROP TABLE users\nSELECT 1",
+            "WITH gone AS (DELETE FROM t WHERE x RETURNING *) SELECT * FROM gone",
+        ] {
+            assert!(reasons(sql).is_empty(), "{}", sql);
+        }
+    }
+
+    #[test]
+    fn test_destructive_statements() {
+        assert_eq!(reasons("drop table users"), vec!["DROP"]);
+        assert_eq!(reasons("TRUNCATE users;"), vec!["TRUNCATE"]);
+        assert_eq!(reasons("ALTER TABLE events DELETE WHERE 1"), vec!["ALTER"]);
+        assert_eq!(reasons("DELETE FROM users"), vec!["DELETE without WHERE"]);
+        assert_eq!(
+            reasons("UPDATE users SET a = (SELECT b FROM c WHERE d)"),
+            vec!["UPDATE without WHERE"]
+        );
+        assert_eq!(
+            reasons("WITH x AS (SELECT 1) DELETE FROM users"),
+            vec!["DELETE without WHERE"]
+        );
+    }
+
+    #[test]
+    fn test_checks_every_statement() {
+        let sql = "SELECT 1; DROP TABLE a; DROP TABLE b; DELETE FROM c";
+        assert_eq!(reasons(sql), vec!["DROP", "DELETE without WHERE"]);
+    }
+
+    #[test]
+    fn test_modifying_ctes() {
+        assert_eq!(
+            reasons("WITH gone AS (DELETE FROM users RETURNING *) SELECT * FROM gone"),
+            vec!["DELETE without WHERE"]
+        );
+        assert_eq!(
+            reasons(
+                "WITH a AS (SELECT 1), b AS MATERIALIZED \
+                 (WITH c AS (UPDATE t SET x = 1 RETURNING id) SELECT * FROM c) SELECT * FROM b"
+            ),
+            vec!["UPDATE without WHERE"]
+        );
+    }
+
+    #[test]
+    fn test_splits_for_dialect() {
+        // A backslash is an ordinary character in Postgres strings, so the DROP is a
+        // statement of its own there
+        let sql = r"SELECT 'C:\'; DROP TABLE users; SELECT 'x'";
+        assert_eq!(destructive_reasons(sql, Dialect::Postgres), vec!["DROP"]);
+        assert!(destructive_reasons(sql, Dialect::Mysql).is_empty());
+    }
+}
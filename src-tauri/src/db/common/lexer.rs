@@ -0,0 +1,289 @@
+use crate::models::DatabaseDriver;
+
+// Lexical rules that differ between the databases we talk to
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Dialect {
+    #[default]
+    Generic,
+    Postgres,
+    Mysql,
+    Clickhouse,
+    Sqlite,
+}
+
+impl From<&DatabaseDriver> for Dialect {
+    fn from(driver: &DatabaseDriver) -> Self {
+        match driver {
+            // DuckDB follows Postgres quoting rules
+            DatabaseDriver::Postgres | DatabaseDriver::Duckdb => Dialect::Postgres,
+            DatabaseDriver::Mysql => Dialect::Mysql,
+            DatabaseDriver::Clickhouse => Dialect::Clickhouse,
+            DatabaseDriver::Sqlite => Dialect::Sqlite,
+        }
+    }
+}
+
+impl Dialect {
+    // Whether backslash escapes a quote in a plain '...' string
+    fn backslash_escapes(self) -> bool {
+        matches!(
+            self,
+            Dialect::Generic | Dialect::Mysql | Dialect::Clickhouse
+        )
+    }
+
+    fn dollar_quotes(self) -> bool {
+        !matches!(self, Dialect::Mysql | Dialect::Sqlite)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TokenKind {
+    Word,
+    String,
+    QuotedIdent,
+    Punct(char),
+}
+
+// A token and its byte range in the source. Comments and whitespace are skipped.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Token {
+    pub kind: TokenKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Token {
+    pub fn text<'a>(&self, sql: &'a str) -> &'a str {
+        &sql[self.start..self.end]
+    }
+
+    pub fn is_word(&self, sql: &str, word: &str) -> bool {
+        self.kind == TokenKind::Word && self.text(sql).eq_ignore_ascii_case(word)
+    }
+}
+
+// Just enough lexing to tell keywords from the contents of strings, quoted names and
+// comments, lenient enough for any of the dialects we support. Unterminated strings
+// and comments run to the end of the input.
+pub fn tokenize(sql: &str) -> Vec<Token> {
+    tokenize_for(sql, Dialect::Generic)
+}
+
+pub fn tokenize_for(sql: &str, dialect: Dialect) -> Vec<Token> {
+    let bytes = sql.as_bytes();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let start = i;
+        let c = bytes[i];
+        let kind = match c {
+            b if b.is_ascii_whitespace() => {
+                i += 1;
+                continue;
+            }
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i = find(bytes, i + 2, b"\n").map_or(bytes.len(), |p| p + 1);
+                continue;
+            }
+            b'/' if bytes.get(i + 1) == Some(&b'*') => {
+                i = skip_block_comment(bytes, i);
+                continue;
+            }
+            b'#' if dialect == Dialect::Mysql => {
+                i = find(bytes, i + 1, b"\n").map_or(bytes.len(), |p| p + 1);
+                continue;
+            }
+            b'\'' => {
+                let backslash = dialect.backslash_escapes() || is_escape_prefix(sql, &tokens, i);
+                i = skip_quoted(bytes, i, b'\'', backslash);
+                TokenKind::String
+            }
+            b'"' | b'`' => {
+                i = skip_quoted(bytes, i, c, false);
+                TokenKind::QuotedIdent
+            }
+            b'$' => match dollar_tag(bytes, i).filter(|_| dialect.dollar_quotes()) {
+                Some(tag_end) => {
+                    let tag = &bytes[i..tag_end];
+                    i = find(bytes, tag_end, tag).map_or(bytes.len(), |p| p + tag.len());
+                    TokenKind::String
+                }
+                None => {
+                    i += 1;
+                    TokenKind::Punct('$')
+                }
+            },
+            b if is_word_byte(b) => {
+                while i < bytes.len() && is_word_byte(bytes[i]) {
+                    i += 1;
+                }
+                TokenKind::Word
+            }
+            _ => {
+                let ch = sql[i..].chars().next().unwrap_or_default();
+                i += ch.len_utf8();
+                TokenKind::Punct(ch)
+            }
+        };
+        tokens.push(Token {
+            kind,
+            start,
+            end: i,
+        });
+    }
+
+    tokens
+}
+
+// Upper-cased keywords of the statement itself, not of subqueries or CTE bodies
+pub fn top_level_words(sql: &str, tokens: &[Token]) -> Vec<String> {
+    let mut depth = 0i32;
+    let mut words = Vec::new();
+    for token in tokens {
+        match token.kind {
+            TokenKind::Punct('(') => depth += 1,
+            TokenKind::Punct(')') => depth -= 1,
+            TokenKind::Word if depth == 0 => words.push(token.text(sql).to_ascii_uppercase()),
+            _ => {}
+        }
+    }
+    words
+}
+
+// The verb of a statement given its top-level words. After a CTE list the main
+// statement follows, so the search goes on past `WITH`.
+pub fn statement_verb<'a>(words: &mut impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    match words.next()? {
+        "WITH" => words.find(|w| matches!(*w, "SELECT" | "INSERT" | "UPDATE" | "DELETE" | "MERGE")),
+        verb => Some(verb),
+    }
+}
+
+// Non-ASCII bytes are treated as word characters so identifiers in any script stay
+// whole; multi-byte characters are never split this way.
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_' || b >= 0x80
+}
+
+// Postgres only honours backslashes in E'...' strings
+fn is_escape_prefix(sql: &str, tokens: &[Token], quote: usize) -> bool {
+    tokens
+        .last()
+        .is_some_and(|t| t.end == quote && t.is_word(sql, "E"))
+}
+
+fn find(bytes: &[u8], from: usize, needle: &[u8]) -> Option<usize> {
+    if from > bytes.len() {
+        return None;
+    }
+    bytes[from..]
+        .windows(needle.len())
+        .position(|w| w == needle)
+        .map(|p| p + from)
+}
+
+// Postgres allows nested block comments
+fn skip_block_comment(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        if bytes[i] == b'/' && bytes.get(i + 1) == Some(&b'*') {
+            depth += 1;
+            i += 2;
+        } else if bytes[i] == b'*' && bytes.get(i + 1) == Some(&b'/') {
+            depth -= 1;
+            i += 2;
+            if depth == 0 {
+                return i;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+// Doubled quotes escape themselves everywhere; backslashes only in strings, where
+// MySQL and ClickHouse treat them as escapes
+fn skip_quoted(bytes: &[u8], start: usize, quote: u8, backslash: bool) -> usize {
+    let mut i = start + 1;
+    while i < bytes.len() {
+        if backslash && bytes[i] == b'\\' {
+            i += 2;
+        } else if bytes[i] == quote {
+            if bytes.get(i + 1) == Some(&quote) {
+                i += 2;
+            } else {
+                return i + 1;
+            }
+        } else {
+            i += 1;
+        }
+    }
+    bytes.len()
+}
+
+// End of a `$tag$` opening a Postgres dollar-quoted string. `$1` parameters are not
+// tags, since a tag can't start with a digit.
+fn dollar_tag(bytes: &[u8], start: usize) -> Option<usize> {
+    let mut i = start + 1;
+    if bytes.get(i).is_some_and(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+        i += 1;
+    }
+    (bytes.get(i) == Some(&b'$')).then_some(i + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn words(sql: &str) -> Vec<&str> {
+        tokenize(sql)
+            .iter()
+            .filter(|t| t.kind == TokenKind::Word)
+            .map(|t| t.text(sql))
+            .collect()
+    }
+
+    #[test]
+    fn test_skips_comments_and_literals() {
+        let sql = "SELECT 'drop; it''s' -- delete\n/* a /* nested */ update */ FROM \"where\" `t`";
+        assert_eq!(words(sql), vec!["SELECT", "FROM"]);
+    }
+
+    #[test]
+    fn test_dollar_quotes() {
+        let sql = "SELECT $body$ DROP TABLE x; $body$, $$ ; $$, $1";
+        let tokens = tokenize(sql);
+        assert_eq!(words(sql), vec!["SELECT", "1"]);
+        assert_eq!(tokens[1].text(sql), "$body$ DROP TABLE x; $body$");
+        assert!(tokens.iter().all(|t| t.kind != TokenKind::Punct(';')));
+    }
+
+    #[test]
+    fn test_backslash_escapes_in_strings() {
+        let sql = r"SELECT 'it\'s; here', x";
+        assert_eq!(words(sql), vec!["SELECT", "x"]);
+    }
+
+    #[test]
+    fn test_dialect_rules() {
+        let sql = r"SELECT 'C:\', E'it\'s', x";
+        let tokens = tokenize_for(sql, Dialect::Postgres);
+        assert_eq!(tokens[1].text(sql), r"'C:\'");
+        assert_eq!(tokens[4].text(sql), r"'it\'s'");
+
+        let sql = "SELECT 1 # comment; DROP\n, $a$ FROM t";
+        let kinds: Vec<_> = tokenize_for(sql, Dialect::Mysql)
+            .iter()
+            .map(|t| t.kind)
+            .collect();
+        assert!(!kinds.contains(&TokenKind::Punct(';')));
+        assert!(!kinds.contains(&TokenKind::String));
+    }
+}
@@ -1,3 +1,19 @@
+mod column_types;
+mod command_tag;
+mod guard;
+mod lexer;
+mod pool;
+mod session;
+mod splitter;
+mod stream;
 mod sql_builder;
 
+pub use column_types::*;
+pub use command_tag::*;
+pub use guard::*;
+pub use lexer::*;
+pub use pool::*;
+pub use session::*;
+pub use splitter::*;
+pub use stream::*;
 pub use sql_builder::*;
@@ -0,0 +1,73 @@
+use sqlx::pool::PoolOptions;
+use std::time::Duration;
+
+use crate::models::ConnectionConfig;
+
+// Pool settings shared by the sqlx drivers. Zero counts as unset everywhere, and the
+// connect timeout doubles as the acquire timeout when none is given.
+pub fn pool_options<DB: sqlx::Database>(
+    config: &ConnectionConfig,
+    default_max_connections: u32,
+) -> PoolOptions<DB> {
+    let mut options = PoolOptions::<DB>::new().max_connections(
+        config
+            .max_connections
+            .filter(|n| *n > 0)
+            .unwrap_or(default_max_connections),
+    );
+    if let Some(timeout) = config
+        .acquire_timeout
+        .or(config.connect_timeout)
+        .filter(|t| *t > 0)
+    {
+        options = options.acquire_timeout(Duration::from_secs(timeout));
+    }
+    if let Some(timeout) = config.idle_timeout.filter(|t| *t > 0) {
+        options = options.idle_timeout(Duration::from_secs(timeout));
+    }
+    options
+}
+
+pub fn statement_timeout(config: &ConnectionConfig) -> Option<Duration> {
+    config
+        .statement_timeout
+        .filter(|t| *t > 0)
+        .map(Duration::from_secs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Postgres;
+
+    #[test]
+    fn test_pool_options_defaults() {
+        let options = pool_options::<Postgres>(&ConnectionConfig::default(), 20);
+        assert_eq!(options.get_max_connections(), 20);
+        assert_eq!(options.get_acquire_timeout(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_pool_options_from_config() {
+        let config = ConnectionConfig {
+            max_connections: Some(3),
+            connect_timeout: Some(5),
+            idle_timeout: Some(60),
+            ..Default::default()
+        };
+        let options = pool_options::<Postgres>(&config, 20);
+        assert_eq!(options.get_max_connections(), 3);
+        assert_eq!(options.get_acquire_timeout(), Duration::from_secs(5));
+        assert_eq!(options.get_idle_timeout(), Some(Duration::from_secs(60)));
+
+        let config = ConnectionConfig {
+            max_connections: Some(0),
+            acquire_timeout: Some(2),
+            connect_timeout: Some(5),
+            ..config
+        };
+        let options = pool_options::<Postgres>(&config, 20);
+        assert_eq!(options.get_max_connections(), 20);
+        assert_eq!(options.get_acquire_timeout(), Duration::from_secs(2));
+    }
+}
@@ -0,0 +1,135 @@
+use super::lexer::{tokenize, Dialect, TokenKind};
+use super::splitter::split_statements;
+use super::validate_identifier;
+use crate::error::{DbError, Result};
+use crate::models::ConnectionConfig;
+
+// Session variables with validated names. Rows left with an empty name are skipped.
+pub fn session_variables(config: &ConnectionConfig) -> Result<Vec<(String, String)>> {
+    let mut variables = Vec::new();
+    for variable in config.session_variables.iter().flatten() {
+        let name = variable.name.trim();
+        if name.is_empty() {
+            continue;
+        }
+        validate_identifier(name)?;
+        variables.push((name.to_string(), variable.value.clone()));
+    }
+    Ok(variables)
+}
+
+// Value of a session variable in a PRAGMA or SET statement. Numbers stay bare, anything
+// else becomes a string literal.
+pub fn setting_literal(value: &str) -> String {
+    if value.parse::<f64>().is_ok_and(f64::is_finite) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+}
+
+pub fn init_statements(config: &ConnectionConfig) -> Vec<String> {
+    config
+        .init_script
+        .as_deref()
+        .map(|script| split_statements(script, Dialect::from(&config.driver)))
+        .unwrap_or_default()
+        .into_iter()
+        .map(str::to_string)
+        .collect()
+}
+
+// ClickHouse over HTTP has no session to run a script in, so the session variables
+// and the `SET name = value` statements of the init script are sent as settings with
+// every request instead
+pub fn clickhouse_settings(config: &ConnectionConfig) -> Result<Vec<(String, String)>> {
+    let mut settings = session_variables(config)?;
+    for statement in init_statements(config) {
+        let setting = parse_set(&statement).ok_or_else(|| {
+            DbError::Config(format!(
+                "ClickHouse init scripts only support SET statements: {}",
+                statement
+            ))
+        })?;
+        settings.push(setting);
+    }
+    Ok(settings)
+}
+
+fn parse_set(statement: &str) -> Option<(String, String)> {
+    let tokens = tokenize(statement);
+    match tokens.as_slice() {
+        [set, name, eq, value @ ..]
+            if set.is_word(statement, "SET")
+                && name.kind == TokenKind::Word
+                && eq.kind == TokenKind::Punct('=')
+                && !value.is_empty() =>
+        {
+            let text = &statement[value[0].start..value[value.len() - 1].end];
+            let value = match value {
+                [literal] if literal.kind == TokenKind::String => {
+                    text[1..text.len() - 1].replace("''", "'")
+                }
+                _ => text.to_string(),
+            };
+            Some((name.text(statement).to_string(), value))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::SessionVariable;
+
+    #[test]
+    fn test_clickhouse_settings() {
+        let config = ConnectionConfig {
+            session_variables: Some(vec![
+                SessionVariable {
+                    name: "max_threads".to_string(),
+                    value: "4".to_string(),
+                },
+                SessionVariable::default(),
+            ]),
+            init_script: Some("SET join_use_nulls = 1;\nSET session_timezone = 'UTC';".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            clickhouse_settings(&config).unwrap(),
+            vec![
+                ("max_threads".to_string(), "4".to_string()),
+                ("join_use_nulls".to_string(), "1".to_string()),
+                ("session_timezone".to_string(), "UTC".to_string()),
+            ]
+        );
+
+        let config = ConnectionConfig {
+            init_script: Some("CREATE TEMPORARY TABLE t (x UInt8)".to_string()),
+            ..Default::default()
+        };
+        assert!(clickhouse_settings(&config).is_err());
+    }
+
+    #[test]
+    fn test_setting_literal() {
+        assert_eq!(setting_literal("-4000"), "-4000");
+        assert_eq!(setting_literal("1.5"), "1.5");
+        assert_eq!(setting_literal("WAL"), "'WAL'");
+        assert_eq!(setting_literal("it's"), "'it''s'");
+        assert_eq!(setting_literal("inf"), "'inf'");
+    }
+
+    #[test]
+    fn test_session_variables_reject_invalid_names() {
+        let config = ConnectionConfig {
+            session_variables: Some(vec![SessionVariable {
+                name: "time_zone; DROP TABLE x".to_string(),
+                value: "UTC".to_string(),
+            }]),
+            ..Default::default()
+        };
+        assert!(session_variables(&config).is_err());
+    }
+}
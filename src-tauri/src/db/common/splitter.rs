@@ -0,0 +1,250 @@
+use super::lexer::{tokenize_for, Dialect, Token, TokenKind};
+
+// Splits a script into statements. Terminators inside strings, quoted names, comments
+// and dollar-quoted bodies don't count, nor do semicolons inside the BEGIN ... END body
+// of a CREATE TRIGGER/PROCEDURE. MySQL scripts may change the terminator with the
+// client-side `DELIMITER` command. Empty statements are dropped.
+pub fn split_statements(sql: &str, dialect: Dialect) -> Vec<&str> {
+    let tokens = tokenize_for(sql, dialect);
+    let mut statements = Vec::new();
+    let mut delimiter = ";";
+    let mut current: Vec<Token> = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let token = tokens[i];
+
+        if current.is_empty() && dialect == Dialect::Mysql && token.is_word(sql, "DELIMITER") {
+            let line_end = sql[token.end..]
+                .find('\n')
+                .map_or(sql.len(), |p| p + token.end);
+            if let Some(new) = sql[token.end..line_end].split_whitespace().next() {
+                delimiter = new;
+            }
+            while i < tokens.len() && tokens[i].start < line_end {
+                i += 1;
+            }
+            continue;
+        }
+
+        let terminates = match token.kind {
+            TokenKind::String | TokenKind::QuotedIdent => false,
+            _ if delimiter == ";" => {
+                token.kind == TokenKind::Punct(';') && block_depth(sql, &current) == 0
+            }
+            _ => sql[token.start..].starts_with(delimiter),
+        };
+
+        if terminates {
+            push_statement(sql, &current, &mut statements);
+            current.clear();
+            let end = token.start + delimiter.len();
+            while i < tokens.len() && tokens[i].start < end {
+                i += 1;
+            }
+        } else {
+            current.push(token);
+            i += 1;
+        }
+    }
+    push_statement(sql, &current, &mut statements);
+
+    statements
+}
+
+fn push_statement<'a>(sql: &'a str, tokens: &[Token], statements: &mut Vec<&'a str>) {
+    if let (Some(first), Some(last)) = (tokens.first(), tokens.last()) {
+        statements.push(&sql[first.start..last.end]);
+    }
+}
+
+// How many BEGIN/CASE blocks are still open in a CREATE PROCEDURE/FUNCTION/TRIGGER/
+// EVENT statement. `END IF`, `END LOOP` and the like close blocks we don't count,
+// while the CASE of a MySQL `END CASE` closes one and doesn't open another.
+fn block_depth(sql: &str, tokens: &[Token]) -> i32 {
+    if !is_routine(sql, tokens) {
+        return 0;
+    }
+
+    let mut depth = 0;
+    let mut i = 0;
+    while i < tokens.len() {
+        let token = tokens[i];
+        if token.is_word(sql, "CASE")
+            || (token.is_word(sql, "BEGIN") && !is_identifier(sql, tokens, i))
+        {
+            depth += 1;
+        } else if token.is_word(sql, "END") && !is_identifier(sql, tokens, i) {
+            let next = tokens.get(i + 1);
+            let closes_other = next.is_some_and(|next| {
+                ["IF", "LOOP", "WHILE", "REPEAT", "FOR"]
+                    .iter()
+                    .any(|word| next.is_word(sql, word))
+            });
+            if next.is_some_and(|next| next.is_word(sql, "CASE")) {
+                i += 1;
+            }
+            if !closes_other {
+                depth -= 1;
+            }
+        }
+        i += 1;
+    }
+    depth
+}
+
+// Whether the statement is `CREATE [OR REPLACE] [DEFINER = user] PROCEDURE|FUNCTION|
+// TRIGGER|EVENT`, the only statements with a BEGIN ... END body
+fn is_routine(sql: &str, tokens: &[Token]) -> bool {
+    if !tokens.first().is_some_and(|t| t.is_word(sql, "CREATE")) {
+        return false;
+    }
+
+    let mut i = 1;
+    while let Some(token) = tokens.get(i) {
+        if ["PROCEDURE", "FUNCTION", "TRIGGER", "EVENT"]
+            .iter()
+            .any(|word| token.is_word(sql, word))
+        {
+            return true;
+        }
+        if token.is_word(sql, "DEFINER") {
+            // DEFINER = user, followed by @host or by the () of CURRENT_USER()
+            i += 3;
+            if tokens
+                .get(i)
+                .is_some_and(|t| matches!(t.kind, TokenKind::Punct('@') | TokenKind::Punct('(')))
+            {
+                i += 2;
+            }
+            continue;
+        }
+        if ![
+            "OR",
+            "REPLACE",
+            "TEMP",
+            "TEMPORARY",
+            "AGGREGATE",
+            "CONSTRAINT",
+        ]
+        .iter()
+        .any(|word| token.is_word(sql, word))
+        {
+            return false;
+        }
+        i += 1;
+    }
+    false
+}
+
+// BEGIN and END are also fine column and parameter names: `t.begin`, `(begin, end)`
+// or `begin DATE`
+fn is_identifier(sql: &str, tokens: &[Token], i: usize) -> bool {
+    const TYPES: [&str; 22] = [
+        "INT",
+        "INTEGER",
+        "BIGINT",
+        "SMALLINT",
+        "TINYINT",
+        "DECIMAL",
+        "NUMERIC",
+        "FLOAT",
+        "DOUBLE",
+        "REAL",
+        "CHAR",
+        "VARCHAR",
+        "TEXT",
+        "DATE",
+        "DATETIME",
+        "TIME",
+        "TIMESTAMP",
+        "TIMESTAMPTZ",
+        "BOOLEAN",
+        "BOOL",
+        "BLOB",
+        "JSON",
+    ];
+
+    let after_dot = i > 0 && tokens[i - 1].kind == TokenKind::Punct('.');
+    let before_punct = tokens.get(i + 1).is_some_and(|next| {
+        matches!(
+            next.kind,
+            TokenKind::Punct(',') | TokenKind::Punct(')') | TokenKind::Punct('.')
+        )
+    });
+    let before_type = tokens
+        .get(i + 1)
+        .is_some_and(|next| TYPES.iter().any(|word| next.is_word(sql, word)));
+    after_dot || before_punct || before_type
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        let sql = "SET search_path = app; ; -- note;\nSELECT ';' AS x;\n\nSELECT $$a;b$$";
+        assert_eq!(
+            split_statements(sql, Dialect::Postgres),
+            vec!["SET search_path = app", "SELECT ';' AS x", "SELECT $$a;b$$"]
+        );
+    }
+
+    #[test]
+    fn test_mysql_delimiter() {
+        let sql = "DELIMITER $$\n\
+                   CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 'a$$b'; END$$\n\
+                   DELIMITER ;\n\
+                   CALL p(); # done;\n";
+        assert_eq!(
+            split_statements(sql, Dialect::Mysql),
+            vec![
+                "CREATE PROCEDURE p() BEGIN SELECT 1; SELECT 'a$$b'; END",
+                "CALL p()"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_trigger_body() {
+        let sql = "CREATE TRIGGER t AFTER INSERT ON a BEGIN \
+                   UPDATE b SET n = CASE WHEN n > 0 THEN n + 1 END; \
+                   DELETE FROM c; END; SELECT 1";
+        let statements = split_statements(sql, Dialect::Sqlite);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("DELETE FROM c; END"));
+        assert_eq!(statements[1], "SELECT 1");
+    }
+
+    #[test]
+    fn test_mysql_case_statement() {
+        let sql = "CREATE PROCEDURE p(x INT) BEGIN \
+                   CASE x WHEN 1 THEN SELECT 'one'; ELSE SELECT 'other'; END CASE; \
+                   SELECT 'done'; END; CALL p(1)";
+        let statements = split_statements(sql, Dialect::Mysql);
+        assert_eq!(statements.len(), 2);
+        assert!(statements[0].ends_with("SELECT 'done'; END"));
+        assert_eq!(statements[1], "CALL p(1)");
+    }
+
+    #[test]
+    fn test_begin_as_column_name() {
+        let sql = "CREATE TABLE periods (begin date, stop date); \
+                   INSERT INTO periods (begin, stop) VALUES ('2024-01-01', '2024-02-01')";
+        assert_eq!(split_statements(sql, Dialect::Mysql).len(), 2);
+
+        let sql = "CREATE PROCEDURE p(begin DATE) BEGIN \
+                   INSERT INTO periods (begin, stop) VALUES (begin, NULL); END; CALL p(NOW())";
+        let statements = split_statements(sql, Dialect::Mysql);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[1], "CALL p(NOW())");
+    }
+
+    #[test]
+    fn test_escaped_quotes_by_dialect() {
+        let sql = r"SELECT 'a\'; SELECT 2";
+        assert_eq!(split_statements(sql, Dialect::Postgres).len(), 2);
+        assert_eq!(split_statements(sql, Dialect::Mysql).len(), 1);
+    }
+}
@@ -62,7 +62,38 @@ pub fn build_update_sql(
     quote_style: QuoteStyle,
 ) -> Result<String> {
     validate_identifier(table_name)?;
+    update_sql(
+        quote_style.quote(table_name),
+        changes,
+        primary_keys,
+        quote_style,
+    )
+}
+
+// Same as build_update_sql, for a table outside the default schema
+pub fn build_qualified_update_sql(
+    schema: &str,
+    table_name: &str,
+    changes: &std::collections::HashMap<String, serde_json::Value>,
+    primary_keys: &std::collections::HashMap<String, serde_json::Value>,
+    quote_style: QuoteStyle,
+) -> Result<String> {
+    validate_identifier(schema)?;
+    validate_identifier(table_name)?;
+    let target = format!(
+        "{}.{}",
+        quote_style.quote(schema),
+        quote_style.quote(table_name)
+    );
+    update_sql(target, changes, primary_keys, quote_style)
+}
 
+fn update_sql(
+    target: String,
+    changes: &std::collections::HashMap<String, serde_json::Value>,
+    primary_keys: &std::collections::HashMap<String, serde_json::Value>,
+    quote_style: QuoteStyle,
+) -> Result<String> {
     if changes.is_empty() {
         return Err(DbError::Config("No changes provided".to_string()));
     }
@@ -85,7 +116,7 @@ pub fn build_update_sql(
 
     Ok(format!(
         "UPDATE {} SET {} WHERE {}",
-        quote_style.quote(table_name),
+        target,
         set_parts.join(", "),
         where_parts.join(" AND ")
     ))
@@ -107,6 +138,15 @@ pub fn build_select_sql(
     ))
 }
 
+// Marks the statement with its query id, which cancel_query looks for among the
+// running queries
+pub fn with_query_id(sql: &str, query_id: Option<String>) -> String {
+    match query_id {
+        Some(qid) => format!("/* query_id: {} */ {}", qid, sql),
+        None => sql.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,118 @@
+use crate::models::{ColumnInfo, RowBatch};
+use std::sync::Arc;
+
+// Rows per batch handed to the sink
+pub const STREAM_BATCH_SIZE: usize = 500;
+
+// Receives the rows of a streamed query in order
+pub type BatchSink = Arc<dyn Fn(RowBatch) + Send + Sync>;
+
+// Groups the rows of a streamed query into batches for the sink and stops taking them
+// at the row cap
+pub struct RowStream {
+    sink: BatchSink,
+    max_rows: usize,
+    columns: Option<Vec<ColumnInfo>>,
+    columns_sent: bool,
+    batch: Vec<Vec<serde_json::Value>>,
+    count: usize,
+    truncated: bool,
+}
+
+impl RowStream {
+    pub fn new(sink: BatchSink, max_rows: usize) -> Self {
+        RowStream {
+            sink,
+            max_rows,
+            columns: None,
+            columns_sent: false,
+            batch: Vec::new(),
+            count: 0,
+            truncated: false,
+        }
+    }
+
+    pub fn needs_columns(&self) -> bool {
+        self.columns.is_none() && !self.columns_sent
+    }
+
+    // Sent along with the first batch
+    pub fn set_columns(&mut self, columns: Vec<ColumnInfo>) {
+        self.columns = Some(columns);
+    }
+
+    // Returns false once the cap is reached: the row is dropped, the result is
+    // truncated and reading should stop
+    pub fn push(&mut self, row: Vec<serde_json::Value>) -> bool {
+        if self.count >= self.max_rows {
+            self.truncated = true;
+            return false;
+        }
+        self.batch.push(row);
+        self.count += 1;
+        if self.batch.len() >= STREAM_BATCH_SIZE {
+            self.flush();
+        }
+        true
+    }
+
+    // Sends what is left and tells whether rows were cut off
+    pub fn finish(mut self) -> bool {
+        self.flush();
+        self.truncated
+    }
+
+    fn flush(&mut self) {
+        if self.batch.is_empty() {
+            return;
+        }
+        self.columns_sent = true;
+        (self.sink)(RowBatch {
+            columns: self.columns.take(),
+            rows: std::mem::take(&mut self.batch),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::common::column_info;
+    use serde_json::json;
+    use std::sync::Mutex;
+
+    #[test]
+    fn test_batches_and_cap() {
+        let batches = Arc::new(Mutex::new(Vec::new()));
+        let received = batches.clone();
+        let sink: BatchSink = Arc::new(move |batch| received.lock().unwrap().push(batch));
+
+        let mut rows = RowStream::new(sink, STREAM_BATCH_SIZE + 10);
+        rows.set_columns(vec![column_info("n", "int4")]);
+        let mut taken = 0;
+        for n in 0..STREAM_BATCH_SIZE * 2 {
+            if !rows.push(vec![json!(n)]) {
+                break;
+            }
+            taken += 1;
+        }
+        assert!(rows.finish());
+        assert_eq!(taken, STREAM_BATCH_SIZE + 10);
+
+        let batches = batches.lock().unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].columns.as_ref().map(Vec::len), Some(1));
+        assert_eq!(batches[0].rows.len(), STREAM_BATCH_SIZE);
+        assert!(batches[1].columns.is_none());
+        assert_eq!(batches[1].rows.len(), 10);
+    }
+
+    #[test]
+    fn test_not_truncated_at_exact_cap() {
+        let sink: BatchSink = Arc::new(|_| {});
+        let mut rows = RowStream::new(sink, 2);
+        assert!(rows.push(vec![json!(1)]));
+        assert!(rows.push(vec![json!(2)]));
+        assert!(!rows.finish());
+    }
+}
@@ -0,0 +1,586 @@
+use crate::db::common::{
+    build_select_sql, build_update_sql, column_info, session_variables, setting_literal,
+    validate_identifier, BatchSink, QuoteStyle, RowStream,
+};
+use crate::db::traits::DatabaseService;
+use crate::error::{DbError, Result};
+use crate::models::{
+    ColumnInfo, ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
+    UpdateResult,
+};
+use async_trait::async_trait;
+use duckdb::types::{TimeUnit, Value};
+use duckdb::{AccessMode, Config, Connection};
+use serde_json::Map;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+const IN_MEMORY: &str = ":memory:";
+
+pub struct DuckdbService {
+    conn: Option<Arc<Mutex<Connection>>>,
+}
+
+impl DuckdbService {
+    pub fn new() -> Self {
+        DuckdbService { conn: None }
+    }
+
+    // DuckDB's API is blocking and a connection can't be shared between threads,
+    // so every call runs on the blocking pool while holding the connection lock.
+    async fn with_conn<T, F>(&self, f: F) -> Result<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&Connection) -> Result<T> + Send + 'static,
+    {
+        let conn = self.conn.clone().ok_or(DbError::NotConnected)?;
+        tokio::task::spawn_blocking(move || {
+            let conn = conn
+                .lock()
+                .map_err(|_| DbError::Connection("DuckDB connection lock poisoned".to_string()))?;
+            f(&conn)
+        })
+        .await
+        .map_err(|e| DbError::Query(e.to_string()))?
+    }
+
+    async fn query_strings(&self, sql: String) -> Result<Vec<String>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn.prepare(&sql).map_err(map_err)?;
+            let mut rows = stmt.query([]).map_err(map_err)?;
+            let mut values = Vec::new();
+            while let Some(row) = rows.next().map_err(map_err)? {
+                values.push(row.get::<_, String>(0).map_err(map_err)?);
+            }
+            Ok(values)
+        })
+        .await
+    }
+
+    async fn switch_database(&mut self, db_name: Option<String>) -> Result<()> {
+        match db_name {
+            Some(db) => self.set_active_database(db).await,
+            None => Ok(()),
+        }
+    }
+}
+
+impl Default for DuckdbService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn map_err(e: duckdb::Error) -> DbError {
+    DbError::Query(e.to_string())
+}
+
+fn run_query(conn: &Connection, sql: &str) -> Result<QueryResult> {
+    let start = Instant::now();
+    let mut data = Vec::new();
+    let columns = query_rows(conn, sql, |_, row| {
+        data.push(row);
+        true
+    })?;
+
+    Ok(QueryResult {
+        rows: data,
+        columns,
+        duration: start.elapsed().as_secs_f64() * 1000.0,
+        ..QueryResult::empty()
+    })
+}
+
+// Hands the rows to `on_row` one at a time, until it returns false
+fn query_rows(
+    conn: &Connection,
+    sql: &str,
+    mut on_row: impl FnMut(&[ColumnInfo], Vec<serde_json::Value>) -> bool,
+) -> Result<Vec<ColumnInfo>> {
+    let mut stmt = conn.prepare(sql).map_err(map_err)?;
+    let mut rows = stmt.query([]).map_err(map_err)?;
+    // DuckDB reports Arrow types, e.g. `Int32` or `Timestamp(Microsecond, None)`
+    let columns: Vec<ColumnInfo> = rows
+        .as_ref()
+        .map(|s| {
+            s.column_names()
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| column_info(name, &s.column_type(idx).to_string()))
+                .collect()
+        })
+        .unwrap_or_default();
+
+    while let Some(row) = rows.next().map_err(map_err)? {
+        let mut values = Vec::with_capacity(columns.len());
+        for idx in 0..columns.len() {
+            let value: Value = row.get(idx).map_err(map_err)?;
+            values.push(value_to_json(value));
+        }
+        if !on_row(&columns, values) {
+            break;
+        }
+    }
+    Ok(columns)
+}
+
+fn to_micros(unit: TimeUnit, value: i64) -> i64 {
+    match unit {
+        TimeUnit::Second => value.saturating_mul(1_000_000),
+        TimeUnit::Millisecond => value.saturating_mul(1_000),
+        TimeUnit::Microsecond => value,
+        TimeUnit::Nanosecond => value / 1_000,
+    }
+}
+
+fn float_to_json(v: f64) -> serde_json::Value {
+    // NaN and infinities have no JSON number form; keep them readable like ClickHouse does.
+    serde_json::Number::from_f64(v)
+        .map(serde_json::Value::Number)
+        .unwrap_or_else(|| serde_json::Value::String(v.to_string()))
+}
+
+fn key_to_string(key: Value) -> String {
+    match value_to_json(key) {
+        serde_json::Value::String(s) => s,
+        other => other.to_string(),
+    }
+}
+
+fn value_to_json(value: Value) -> serde_json::Value {
+    match value {
+        Value::Null => serde_json::Value::Null,
+        Value::Boolean(b) => serde_json::Value::Bool(b),
+        Value::TinyInt(v) => serde_json::Value::Number(v.into()),
+        Value::SmallInt(v) => serde_json::Value::Number(v.into()),
+        Value::Int(v) => serde_json::Value::Number(v.into()),
+        Value::BigInt(v) => serde_json::Value::Number(v.into()),
+        Value::UTinyInt(v) => serde_json::Value::Number(v.into()),
+        Value::USmallInt(v) => serde_json::Value::Number(v.into()),
+        Value::UInt(v) => serde_json::Value::Number(v.into()),
+        Value::UBigInt(v) => serde_json::Value::Number(v.into()),
+        // 128-bit integers don't fit a JSON number without losing precision
+        Value::HugeInt(v) => serde_json::Value::String(v.to_string()),
+        Value::Float(v) => float_to_json(v as f64),
+        Value::Double(v) => float_to_json(v),
+        Value::Decimal(v) => serde_json::Value::String(v.to_string()),
+        Value::Timestamp(unit, v) => chrono::DateTime::from_timestamp_micros(to_micros(unit, v))
+            .map(|t| serde_json::Value::String(t.naive_utc().to_string()))
+            .unwrap_or(serde_json::Value::Null),
+        Value::Text(s) => serde_json::Value::String(s),
+        Value::Enum(s) => serde_json::Value::String(s),
+        Value::Blob(bytes) => serde_json::Value::String(format!("(binary {} bytes)", bytes.len())),
+        Value::Date32(days) => chrono::NaiveDate::from_ymd_opt(1970, 1, 1)
+            .and_then(|epoch| epoch.checked_add_signed(chrono::Duration::days(days as i64)))
+            .map(|d| serde_json::Value::String(d.to_string()))
+            .unwrap_or(serde_json::Value::Null),
+        Value::Time64(unit, v) => {
+            let micros = to_micros(unit, v);
+            chrono::NaiveTime::from_num_seconds_from_midnight_opt(
+                (micros / 1_000_000) as u32,
+                ((micros % 1_000_000) * 1_000) as u32,
+            )
+            .map(|t| serde_json::Value::String(t.to_string()))
+            .unwrap_or(serde_json::Value::Null)
+        }
+        Value::Interval { months, days, nanos } => serde_json::Value::String(format!(
+            "{} months, {} days, {} us",
+            months,
+            days,
+            nanos / 1_000
+        )),
+        Value::List(items) | Value::Array(items) => {
+            serde_json::Value::Array(items.into_iter().map(value_to_json).collect())
+        }
+        Value::Struct(fields) => {
+            let mut obj = Map::new();
+            for (k, v) in fields.iter() {
+                obj.insert(k.clone(), value_to_json(v.clone()));
+            }
+            serde_json::Value::Object(obj)
+        }
+        Value::Map(entries) => {
+            let mut obj = Map::new();
+            for (k, v) in entries.iter() {
+                obj.insert(key_to_string(k.clone()), value_to_json(v.clone()));
+            }
+            serde_json::Value::Object(obj)
+        }
+        Value::Union(inner) => value_to_json(*inner),
+    }
+}
+
+#[async_trait]
+impl DatabaseService for DuckdbService {
+    async fn connect(&mut self, config: &ConnectionConfig) -> Result<String> {
+        let path = config.database.trim().to_string();
+        let read_only = config.read_only.unwrap_or(false);
+        let init_script = config.init_script.clone().unwrap_or_default();
+        let settings: Vec<String> = session_variables(config)?
+            .into_iter()
+            .map(|(name, value)| format!("SET {} = {}", name, setting_literal(&value)))
+            .collect();
+        let in_memory = path.is_empty() || path == IN_MEMORY;
+        if in_memory && read_only {
+            return Err(DbError::Config(
+                "An in-memory DuckDB database can't be opened read-only".to_string(),
+            ));
+        }
+        let conn = tokio::task::spawn_blocking(move || {
+            let conn = if in_memory {
+                Connection::open_in_memory()
+            } else if read_only {
+                Config::default()
+                    .access_mode(AccessMode::ReadOnly)
+                    .and_then(|config| Connection::open_with_flags(&path, config))
+            } else {
+                Connection::open(&path)
+            }?;
+            // A single connection, so the settings and script only have to run once
+            for sql in &settings {
+                conn.execute_batch(sql)?;
+            }
+            if !init_script.trim().is_empty() {
+                conn.execute_batch(&init_script)?;
+            }
+            Ok::<_, duckdb::Error>(conn)
+        })
+        .await
+        .map_err(|e| DbError::Connection(e.to_string()))?
+        .map_err(|e| DbError::Connection(e.to_string()))?;
+
+        self.conn = Some(Arc::new(Mutex::new(conn)));
+        Ok("Connected to DuckDB".to_string())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        self.conn = None;
+        Ok(())
+    }
+
+    async fn execute(&self, sql: &str, _query_id: Option<String>) -> Result<QueryResult> {
+        let start = Instant::now();
+        let sql = sql.to_string();
+        match self.with_conn(move |conn| run_query(conn, &sql)).await {
+            Ok(result) => Ok(result),
+            Err(DbError::NotConnected) => Err(DbError::NotConnected),
+            Err(e) => {
+                let duration = start.elapsed().as_secs_f64() * 1000.0;
+                Ok(QueryResult::with_error(e.to_string(), duration))
+            }
+        }
+    }
+
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        _query_id: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult> {
+        let start = Instant::now();
+        let sql = sql.to_string();
+        let streamed = self
+            .with_conn(move |conn| {
+                let mut rows = RowStream::new(sink, max_rows);
+                let columns = query_rows(conn, &sql, |columns, row| {
+                    if rows.needs_columns() {
+                        rows.set_columns(columns.to_vec());
+                    }
+                    rows.push(row)
+                });
+                let truncated = rows.finish();
+                columns.map(|columns| (columns, truncated))
+            })
+            .await;
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+        match streamed {
+            Ok((columns, truncated)) => Ok(QueryResult {
+                columns,
+                duration,
+                truncated,
+                ..QueryResult::empty()
+            }),
+            Err(DbError::NotConnected) => Err(DbError::NotConnected),
+            Err(e) => Ok(QueryResult::with_error(e.to_string(), duration)),
+        }
+    }
+
+    async fn cancel_query(&self, _query_id: String) -> Result<()> {
+        Err(DbError::Query(
+            "Cancelling queries is not supported for DuckDB".to_string(),
+        ))
+    }
+
+    async fn get_tables(&mut self, db_name: Option<String>) -> Result<Vec<String>> {
+        self.switch_database(db_name).await?;
+        self.query_strings(
+            "SELECT table_name FROM information_schema.tables WHERE table_catalog = current_database() AND table_schema = current_schema() ORDER BY table_name"
+                .to_string(),
+        )
+        .await
+    }
+
+    async fn get_databases(&self) -> Result<Vec<String>> {
+        self.query_strings(
+            "SELECT database_name FROM duckdb_databases() WHERE NOT internal ORDER BY database_name"
+                .to_string(),
+        )
+        .await
+    }
+
+    async fn get_schema(&mut self, db_name: Option<String>) -> Result<DbSchema> {
+        self.switch_database(db_name).await?;
+        self.with_conn(|conn| {
+            let mut stmt = conn
+                .prepare(
+                    "
+                    SELECT table_name, column_name
+                    FROM information_schema.columns
+                    WHERE table_catalog = current_database() AND table_schema = current_schema()
+                    ORDER BY table_name, ordinal_position
+                ",
+                )
+                .map_err(map_err)?;
+            let mut rows = stmt.query([]).map_err(map_err)?;
+
+            let mut schema: DbSchema = HashMap::new();
+            while let Some(row) = rows.next().map_err(map_err)? {
+                let table: String = row.get(0).map_err(map_err)?;
+                let column: String = row.get(1).map_err(map_err)?;
+                schema.entry(table).or_default().push(column);
+            }
+            Ok(schema)
+        })
+        .await
+    }
+
+    async fn get_table_data(&self, req: DataRequest) -> Result<QueryResult> {
+        let sql = build_select_sql(&req.table_name, req.limit, req.offset, QuoteStyle::DoubleQuote)?;
+        self.execute(&sql, None).await
+    }
+
+    async fn set_active_database(&mut self, db_name: String) -> Result<()> {
+        validate_identifier(&db_name)?;
+        let use_sql = format!("USE {}", QuoteStyle::DoubleQuote.quote(&db_name));
+        self.with_conn(move |conn| conn.execute_batch(&use_sql).map_err(map_err))
+            .await
+    }
+
+    async fn get_primary_keys(&self, table_name: String) -> Result<Vec<String>> {
+        self.with_conn(move |conn| {
+            let mut stmt = conn
+                .prepare(
+                    "
+                    SELECT unnest(constraint_column_names)
+                    FROM duckdb_constraints()
+                    WHERE constraint_type = 'PRIMARY KEY'
+                    AND database_name = current_database()
+                    AND schema_name = current_schema()
+                    AND table_name = ?
+                ",
+                )
+                .map_err(map_err)?;
+            let mut rows = stmt.query([table_name]).map_err(map_err)?;
+
+            let mut keys = Vec::new();
+            while let Some(row) = rows.next().map_err(map_err)? {
+                keys.push(row.get::<_, String>(0).map_err(map_err)?);
+            }
+            Ok(keys)
+        })
+        .await
+    }
+
+    async fn update_rows(&self, updates: Vec<RowUpdate>) -> Result<UpdateResult> {
+        let mut statements = Vec::new();
+        for update in updates {
+            if update.changes.is_empty() {
+                continue;
+            }
+            statements.push(build_update_sql(
+                &update.table_name,
+                &update.changes,
+                &update.primary_keys,
+                QuoteStyle::DoubleQuote,
+            )?);
+        }
+
+        let affected = self
+            .with_conn(move |conn| {
+                let mut affected = 0;
+                for sql in statements {
+                    affected += conn.execute(&sql, []).map_err(map_err)? as u64;
+                }
+                Ok(affected)
+            })
+            .await?;
+
+        Ok(UpdateResult::success(affected))
+    }
+
+    async fn get_dashboard_metrics(&self) -> Result<DashboardMetrics> {
+        let version = self
+            .query_strings("SELECT version()".to_string())
+            .await?
+            .into_iter()
+            .next()
+            .unwrap_or_else(|| "unknown".to_string());
+
+        let (db_size, wal_size, memory_usage) = self
+            .with_conn(|conn| {
+                conn.query_row(
+                    "SELECT database_size, wal_size, memory_usage FROM pragma_database_size() WHERE database_name = current_database()",
+                    [],
+                    |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?)),
+                )
+                .map_err(map_err)
+            })
+            .await?;
+
+        let table_count: i64 = self
+            .with_conn(|conn| {
+                conn.query_row(
+                    "SELECT count(*) FROM duckdb_tables() WHERE database_name = current_database()",
+                    [],
+                    |row| row.get(0),
+                )
+                .map_err(map_err)
+            })
+            .await?;
+
+        let mut extra = HashMap::new();
+        extra.insert("walSize".to_string(), wal_size);
+        extra.insert("memoryUsage".to_string(), memory_usage);
+
+        Ok(DashboardMetrics {
+            version,
+            uptime: 0,
+            active_connections: 1,
+            max_connections: 1,
+            db_size,
+            indexes_size: "N/A".to_string(),
+            table_count: table_count as i32,
+            cache_hit_ratio: 0.0,
+            top_queries: Vec::new(),
+            extra,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::DatabaseDriver;
+    use serde_json::json;
+
+    async fn in_memory_service() -> DuckdbService {
+        let config = ConnectionConfig {
+            id: "duck".to_string(),
+            driver: DatabaseDriver::Duckdb,
+            database: IN_MEMORY.to_string(),
+            ..Default::default()
+        };
+
+        let mut service = DuckdbService::new();
+        service.connect(&config).await.unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_duckdb_in_memory_rejects_read_only() {
+        let config = ConnectionConfig {
+            driver: DatabaseDriver::Duckdb,
+            database: IN_MEMORY.to_string(),
+            read_only: Some(true),
+            ..Default::default()
+        };
+
+        let mut service = DuckdbService::new();
+        let err = service.connect(&config).await.unwrap_err();
+        assert!(matches!(err, DbError::Config(_)), "{}", err);
+        assert!(service.conn.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_duckdb_nested_types() {
+        let service = in_memory_service().await;
+
+        let result = service
+            .execute(
+                "SELECT [1, 2, 3] AS l, {'a': 1, 'b': 'x'} AS s, MAP {'k': 1} AS m, 1.50::DECIMAL(10, 2) AS d, DATE '2024-01-31' AS dt",
+                None,
+            )
+            .await
+            .unwrap();
+
+        assert!(result.error.is_none(), "{:?}", result.error);
+        let names: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["l", "s", "m", "d", "dt"]);
+        assert_eq!(
+            result.rows[0],
+            vec![
+                json!([1, 2, 3]),
+                json!({"a": 1, "b": "x"}),
+                json!({"k": 1}),
+                json!("1.50"),
+                json!("2024-01-31")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_duckdb_schema_and_update() {
+        let mut service = in_memory_service().await;
+
+        service
+            .execute("CREATE TABLE users (id INTEGER PRIMARY KEY, name VARCHAR)", None)
+            .await
+            .unwrap();
+        service
+            .execute("INSERT INTO users VALUES (1, 'Alice'), (2, 'Bob')", None)
+            .await
+            .unwrap();
+        // Same table name in another schema must not leak into the lookups
+        service.execute("CREATE SCHEMA other", None).await.unwrap();
+        service
+            .execute("CREATE TABLE other.users (email VARCHAR PRIMARY KEY)", None)
+            .await
+            .unwrap();
+
+        assert_eq!(service.get_tables(None).await.unwrap(), vec!["users".to_string()]);
+        let schema = service.get_schema(None).await.unwrap();
+        assert_eq!(schema.get("users").unwrap(), &vec!["id".to_string(), "name".to_string()]);
+        assert_eq!(
+            service.get_primary_keys("users".to_string()).await.unwrap(),
+            vec!["id".to_string()]
+        );
+
+        let mut changes = HashMap::new();
+        changes.insert("name".to_string(), json!("Bobby"));
+        let mut pks = HashMap::new();
+        pks.insert("id".to_string(), json!(2));
+        let res = service
+            .update_rows(vec![RowUpdate {
+                table_name: "users".to_string(),
+                primary_keys: pks,
+                changes,
+            }])
+            .await
+            .unwrap();
+        assert_eq!(res.affected_rows, 1);
+
+        let data = service
+            .get_table_data(DataRequest {
+                table_name: "users".to_string(),
+                offset: 1,
+                limit: 1,
+                sort: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(data.rows.len(), 1);
+    }
+}
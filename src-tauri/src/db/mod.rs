@@ -1,27 +1,34 @@
 pub mod common;
 mod mysql;
 mod clickhouse;
+mod duckdb;
 mod postgres;
+pub mod proxy;
+mod sqlite;
 pub mod ssh;
 mod traits;
 
 pub use mysql::MysqlService;
 pub use clickhouse::ClickhouseService;
+pub use duckdb::DuckdbService;
 pub use postgres::PostgresService;
+pub use sqlite::SqliteService;
 pub use traits::DatabaseService;
 
 use crate::error::{DbError, Result};
 use crate::models::{
-    ConnectionConfig, DashboardMetrics, DatabaseDriver, DataRequest, DbSchema, QueryResult,
-    RowUpdate, UpdateResult,
+    ConfirmationRequest, ConnectionConfig, ConnectionEvent, ConnectionState, DashboardMetrics,
+    DatabaseDriver, DataRequest, DbSchema, Environment, QueryResult, RowBatch, RowUpdate,
+    ScriptResult, SslMode, StatementStatus, TunnelState, TunnelStatus, UpdateResult,
 };
-use ssh::SshTunnelService;
+use common::{BatchSink, Dialect};
+use proxy::{ProxySettings, ProxyTunnelService};
+use ssh::{KnownHostsStore, SshTunnelService};
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::Arc;
-
-
-
-
+use std::time::{Duration, Instant};
 
 #[cfg_attr(test, mockall::automock)]
 pub trait DatabaseServiceFactory: Send + Sync {
@@ -37,14 +44,41 @@ impl DatabaseServiceFactory for DefaultDatabaseServiceFactory {
 
             DatabaseDriver::Postgres => Box::new(PostgresService::new()),
             DatabaseDriver::Clickhouse => Box::new(ClickhouseService::new()),
+            DatabaseDriver::Sqlite => Box::new(SqliteService::new()),
+            DatabaseDriver::Duckdb => Box::new(DuckdbService::new()),
         }
     }
 }
 
+type SharedService = Arc<tokio::sync::RwLock<Box<dyn DatabaseService>>>;
+type EventHandler = Arc<dyn Fn(ConnectionEvent) + Send + Sync>;
+
+const KEEPALIVE_INTERVAL: Duration = Duration::from_secs(60);
+const PING_TIMEOUT: Duration = Duration::from_secs(10);
+const MAX_RECONNECT_ATTEMPTS: u32 = 3;
+const RECONNECT_DELAY: Duration = Duration::from_millis(500);
+const CONFIRMATION_TTL: Duration = Duration::from_secs(300);
+
+// A destructive statement held back on a prod connection, waiting for its token
+struct PendingConfirmation {
+    connection_id: String,
+    sql: String,
+    issued: Instant,
+}
+
 #[derive(Clone)]
 pub struct DatabaseManager {
-    services: Arc<tokio::sync::RwLock<HashMap<String, Arc<tokio::sync::RwLock<Box<dyn DatabaseService>>>>>>,
+    services: Arc<tokio::sync::RwLock<HashMap<String, SharedService>>>,
     ssh_services: Arc<tokio::sync::RwLock<HashMap<String, SshTunnelService>>>,
+    proxy_services: Arc<tokio::sync::RwLock<HashMap<String, ProxyTunnelService>>>,
+    // Configs as given to `connect`, before tunnels rewrote host and port. Kept for
+    // reconnecting until the connection is closed on purpose.
+    configs: Arc<tokio::sync::RwLock<HashMap<String, ConnectionConfig>>>,
+    keepalives: Arc<std::sync::Mutex<HashMap<String, tokio::task::JoinHandle<()>>>>,
+    reconnect_locks: Arc<std::sync::Mutex<HashMap<String, Arc<tokio::sync::Mutex<()>>>>>,
+    event_handler: Arc<std::sync::RwLock<Option<EventHandler>>>,
+    known_hosts: Arc<std::sync::RwLock<KnownHostsStore>>,
+    confirmations: Arc<std::sync::Mutex<HashMap<String, PendingConfirmation>>>,
     factory: Arc<Box<dyn DatabaseServiceFactory>>,
 }
 
@@ -57,39 +91,121 @@ impl DatabaseManager {
         DatabaseManager {
             services: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
             ssh_services: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            proxy_services: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            configs: Arc::new(tokio::sync::RwLock::new(HashMap::new())),
+            keepalives: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            reconnect_locks: Arc::new(std::sync::Mutex::new(HashMap::new())),
+            event_handler: Arc::new(std::sync::RwLock::new(None)),
+            known_hosts: Arc::new(std::sync::RwLock::new(KnownHostsStore::new())),
+            confirmations: Arc::new(std::sync::Mutex::new(HashMap::new())),
             factory: Arc::new(factory),
         }
     }
 
-    pub async fn connect(&self, id: String, mut config: ConnectionConfig) -> Result<String> {
+    pub fn set_event_handler(&self, handler: impl Fn(ConnectionEvent) + Send + Sync + 'static) {
+        *self.event_handler.write().unwrap() = Some(Arc::new(handler));
+    }
+
+    // Where SSH host keys are looked up and trusted, ~/.gridly and ~/.ssh by default
+    pub fn set_known_hosts(&self, known_hosts: KnownHostsStore) {
+        *self.known_hosts.write().unwrap() = known_hosts;
+    }
+
+    pub async fn connect(&self, id: String, config: ConnectionConfig) -> Result<String> {
         // Disconnect first (needs write lock on maps)
         self.disconnect(id.clone()).await?;
 
-        if config.use_ssh.unwrap_or(false) && config.ssh_host.is_some() {
-            let mut ssh = SshTunnelService::new();
+        let file_based = config.driver.is_file_based();
+        self.open(&id, config.clone()).await?;
+        self.configs.write().await.insert(id.clone(), config);
+        if !file_based {
+            self.start_keepalive(&id);
+        }
+
+        Ok("Connected".to_string())
+    }
+
+    // Sets up tunnels and the driver service for `id`, without touching the stored config
+    async fn open(&self, id: &str, mut config: ConnectionConfig) -> Result<SharedService> {
+        if config.use_ssh.unwrap_or(false)
+            && config.ssh_host.is_some()
+            && !config.driver.is_file_based()
+        {
+            reject_hostname_verification(&config, "an SSH tunnel")?;
+            let known_hosts = self.known_hosts.read().unwrap().clone();
+            let mut ssh = SshTunnelService::with_known_hosts(known_hosts);
             let remote_port = config.port;
             let remote_host = config.host.clone();
             let local_port = ssh.create_tunnel(&config, &remote_host, remote_port).await?;
 
-            self.ssh_services.write().await.insert(id.clone(), ssh);
+            self.ssh_services.write().await.insert(id.to_string(), ssh);
 
             config.host = "127.0.0.1".to_string();
             config.port = local_port;
+            // The bastion connection already went through the proxy
+            config.use_proxy = Some(false);
+        } else if !config.driver.is_file_based() && config.driver != DatabaseDriver::Clickhouse {
+            // ClickHouse speaks HTTP and hands the proxy to its client instead
+            if let Some(settings) = ProxySettings::from_config(&config)? {
+                reject_hostname_verification(&config, "a proxy")?;
+                let mut proxy = ProxyTunnelService::new();
+                let timeout = config.connect_timeout.map(Duration::from_secs);
+                let local_port = proxy
+                    .create_tunnel(&settings, &config.host, config.port, timeout)
+                    .await?;
+
+                self.proxy_services.write().await.insert(id.to_string(), proxy);
+
+                config.host = "127.0.0.1".to_string();
+                config.port = local_port;
+                config.use_proxy = Some(false);
+            }
         }
 
         let mut service = self.factory.create(&config.driver);
-        service.connect(&config).await?;
+        if let Err(e) = service.connect(&config).await {
+            // Through a tunnel or proxy the driver usually only sees a reset connection,
+            // the forwarder knows the actual cause
+            let tunnel = self.ssh_services.write().await.remove(id);
+            if let Some(mut ssh) = tunnel {
+                let last_error = ssh.status().last_error;
+                ssh.close();
+                if let Some(error) = last_error {
+                    return Err(DbError::Ssh(error));
+                }
+            }
+            let proxy = self.proxy_services.write().await.remove(id);
+            if let Some(mut proxy) = proxy {
+                let last_error = proxy.last_error();
+                proxy.close();
+                if let Some(error) = last_error {
+                    return Err(DbError::Proxy(error));
+                }
+            }
+            return Err(e);
+        }
 
         // Store the service wrapped in Arc<RwLock>
-        self.services.write().await.insert(id, Arc::new(tokio::sync::RwLock::new(service)));
+        let service: SharedService = Arc::new(tokio::sync::RwLock::new(service));
+        self.services.write().await.insert(id.to_string(), service.clone());
 
-        Ok("Connected".to_string())
+        Ok(service)
     }
 
     pub async fn disconnect(&self, id: String) -> Result<()> {
+        if let Some(keepalive) = self.keepalives.lock().unwrap().remove(&id) {
+            keepalive.abort();
+        }
+        self.configs.write().await.remove(&id);
+        self.reconnect_locks.lock().unwrap().remove(&id);
+        self.close(&id).await;
+        Ok(())
+    }
+
+    async fn close(&self, id: &str) {
         {
             let mut services = self.services.write().await;
-            if let Some(service_lock) = services.remove(&id) {
+            if let Some(service_lock) = services.remove(id) {
                 // We need to acquire write lock to call disconnect which takes &mut self
                 let mut service = service_lock.write().await;
                 let _ = service.disconnect().await;
@@ -98,101 +214,518 @@ impl DatabaseManager {
 
         {
             let mut ssh_services = self.ssh_services.write().await;
-            if let Some(mut ssh) = ssh_services.remove(&id) {
+            if let Some(mut ssh) = ssh_services.remove(id) {
                 ssh.close();
             }
         }
 
-        Ok(())
+        {
+            let mut proxy_services = self.proxy_services.write().await;
+            if let Some(mut proxy) = proxy_services.remove(id) {
+                proxy.close();
+            }
+        }
     }
 
-    // Helper to get read access to a service
-    async fn get_service_read(&self, id: &str) -> Result<Arc<tokio::sync::RwLock<Box<dyn DatabaseService>>>> {
-        let services = self.services.read().await;
-        services.get(id).cloned().ok_or_else(|| DbError::ConnectionNotFound(id.to_string()))
+    pub async fn get_tunnel_status(&self, id: &str) -> Option<TunnelStatus> {
+        self.ssh_services.read().await.get(id).map(|ssh| ssh.status())
     }
 
-    // Helper to get write access to a service
-    // Note: This returns the Arc to the lock, same as read, but we intend to write lock it.
-    // Separating just for consistent naming if needed, but actually we just need the arc.
-    
-    pub async fn execute(&self, id: String, sql: String, query_id: Option<String>) -> Result<QueryResult> {
-        let service_lock = self.get_service_read(&id).await?;
-        // execute takes &self, so we only need read lock on the service
-        let service = service_lock.read().await;
-        service.execute(&sql, query_id).await
+    pub async fn list_tunnels(&self) -> HashMap<String, TunnelStatus> {
+        self.ssh_services
+            .read()
+            .await
+            .iter()
+            .map(|(id, ssh)| (id.clone(), ssh.status()))
+            .collect()
+    }
+
+    // Pings idle connections so a connection that died while nobody was using it, e.g.
+    // across a laptop sleep, is restored before the next query needs it
+    fn start_keepalive(&self, id: &str) {
+        let manager = self.clone();
+        let task_id = id.to_string();
+        let handle = tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(KEEPALIVE_INTERVAL).await;
+                let service = manager.services.read().await.get(&task_id).cloned();
+                let alive = match &service {
+                    // A query holding the service busy is proof enough
+                    Some(service) => match service.try_read() {
+                        Ok(guard) => ping(guard.as_ref()).await,
+                        Err(_) => true,
+                    },
+                    None => false,
+                };
+                if !alive {
+                    // Failures are reported through events, the next tick tries again
+                    let _ = manager.reconnect(&task_id, service.as_ref()).await;
+                }
+            }
+        });
+        if let Some(old) = self
+            .keepalives
+            .lock()
+            .unwrap()
+            .insert(id.to_string(), handle)
+        {
+            old.abort();
+        }
+    }
+
+    async fn is_alive(&self, id: &str, service: &SharedService) -> bool {
+        if let Some(status) = self.get_tunnel_status(id).await {
+            if matches!(status.state, TunnelState::Failed | TunnelState::Closed) {
+                return false;
+            }
+        }
+        let service = service.read().await;
+        ping(service.as_ref()).await
+    }
+
+    // Replaces the service of `id` with a fresh one built from the stored config.
+    // Callers pass the service they saw failing; if another caller already replaced it
+    // in the meantime, the new one is returned as is.
+    async fn reconnect(&self, id: &str, failed: Option<&SharedService>) -> Result<SharedService> {
+        let lock = self
+            .reconnect_locks
+            .lock()
+            .unwrap()
+            .entry(id.to_string())
+            .or_default()
+            .clone();
+        let _guard = lock.lock().await;
+
+        let current = self.services.read().await.get(id).cloned();
+        if let Some(current) = current {
+            if !failed.is_some_and(|failed| Arc::ptr_eq(failed, &current)) {
+                return Ok(current);
+            }
+        }
+        let config = self
+            .configs
+            .read()
+            .await
+            .get(id)
+            .cloned()
+            .ok_or_else(|| DbError::ConnectionNotFound(id.to_string()))?;
+
+        let mut delay = RECONNECT_DELAY;
+        let mut last_error = None;
+        for attempt in 1..=MAX_RECONNECT_ATTEMPTS {
+            self.emit(
+                id,
+                ConnectionState::Reconnecting,
+                attempt,
+                last_error.as_ref(),
+            );
+            self.close(id).await;
+            match self.open(id, config.clone()).await {
+                Ok(service) => {
+                    // Disconnected on purpose while we were busy
+                    if !self.configs.read().await.contains_key(id) {
+                        self.close(id).await;
+                        return Err(DbError::ConnectionNotFound(id.to_string()));
+                    }
+                    log::info!("Reconnected {} after {} attempt(s)", id, attempt);
+                    self.emit(id, ConnectionState::Connected, attempt, None);
+                    return Ok(service);
+                }
+                Err(e) => {
+                    log::warn!("Reconnect attempt {} for {} failed: {}", attempt, id, e);
+                    last_error = Some(e);
+                }
+            }
+            if attempt < MAX_RECONNECT_ATTEMPTS {
+                tokio::time::sleep(delay).await;
+                delay *= 2;
+            }
+        }
+
+        let error = last_error.unwrap_or(DbError::NotConnected);
+        self.emit(
+            id,
+            ConnectionState::Failed,
+            MAX_RECONNECT_ATTEMPTS,
+            Some(&error),
+        );
+        Err(error)
+    }
+
+    fn emit(&self, id: &str, state: ConnectionState, attempt: u32, error: Option<&DbError>) {
+        let handler = self.event_handler.read().unwrap().clone();
+        if let Some(handler) = handler {
+            handler(ConnectionEvent {
+                connection_id: id.to_string(),
+                state,
+                attempt,
+                error: error.map(|e| e.to_string()),
+            });
+        }
+    }
+
+    // Helper to get the service of a connection. A connection whose last reconnect
+    // failed has no service but still has its config, so it gets another try here.
+    async fn get_service(&self, id: &str) -> Result<SharedService> {
+        let service = self.services.read().await.get(id).cloned();
+        match service {
+            Some(service) => Ok(service),
+            None if self.configs.read().await.contains_key(id) => self.reconnect(id, None).await,
+            None => Err(DbError::ConnectionNotFound(id.to_string())),
+        }
+    }
+
+    // Runs `f` against the service and, if it fails because the connection is gone,
+    // reconnects and runs it once more. With `retry` off the connection is still
+    // restored, but the error is returned, for calls that must not run twice.
+    async fn with_service<T, F, Fut>(&self, id: &str, retry: bool, f: F) -> Result<T>
+    where
+        F: Fn(SharedService) -> Fut,
+        Fut: Future<Output = Result<T>>,
+    {
+        let service = self.get_service(id).await?;
+        let error = match f(service.clone()).await {
+            Ok(value) => return Ok(value),
+            Err(e) => e,
+        };
+        if !self.configs.read().await.contains_key(id) || self.is_alive(id, &service).await {
+            return Err(error);
+        }
+
+        let service = self.reconnect(id, Some(&service)).await?;
+        if retry {
+            f(service).await
+        } else {
+            Err(error)
+        }
+    }
+
+    pub async fn execute(
+        &self,
+        id: String,
+        sql: String,
+        query_id: Option<String>,
+    ) -> Result<QueryResult> {
+        self.execute_confirmed(id, sql, query_id, None).await
+    }
+
+    // On prod connections destructive statements only run when re-submitted with the
+    // token from the confirmation result issued for the same connection and SQL
+    pub async fn execute_confirmed(
+        &self,
+        id: String,
+        sql: String,
+        query_id: Option<String>,
+        confirm_token: Option<String>,
+    ) -> Result<QueryResult> {
+        if let Some(confirmation) = self.require_confirmation(&id, &sql, confirm_token).await {
+            return Ok(QueryResult::confirmation_required(confirmation));
+        }
+
+        // The server may have applied the statement before the connection dropped, so
+        // it is never run a second time
+        let mut result = self
+            .with_service(&id, false, |service| {
+                let sql = sql.clone();
+                let query_id = query_id.clone();
+                async move {
+                    // execute takes &self, so we only need read lock on the service
+                    let service = service.read().await;
+                    service.execute(&sql, query_id).await
+                }
+            })
+            .await?;
+        if self.has_command_tags(&id).await {
+            let row_count = result.rows.len();
+            result.synthetic_command_tag = common::synthetic_command_tag(&sql, &result, row_count);
+        }
+        Ok(result)
+    }
+
+    // Like execute_confirmed, but the rows go to `sink` in batches as they are read and
+    // stop at `max_rows`. Not retried after a reconnect either.
+    pub async fn execute_stream(
+        &self,
+        id: String,
+        sql: String,
+        query_id: Option<String>,
+        confirm_token: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult> {
+        if let Some(confirmation) = self.require_confirmation(&id, &sql, confirm_token).await {
+            return Ok(QueryResult::confirmation_required(confirmation));
+        }
+
+        let row_count = Arc::new(AtomicUsize::new(0));
+        let counted = row_count.clone();
+        let sink: BatchSink = Arc::new(move |batch: RowBatch| {
+            counted.fetch_add(batch.rows.len(), Ordering::Relaxed);
+            sink(batch);
+        });
+
+        let mut result = self
+            .with_service(&id, false, |service| {
+                let sql = sql.clone();
+                let query_id = query_id.clone();
+                let sink = sink.clone();
+                async move {
+                    let service = service.read().await;
+                    service.execute_stream(&sql, query_id, max_rows, sink).await
+                }
+            })
+            .await?;
+        if self.has_command_tags(&id).await {
+            let row_count = row_count.load(Ordering::Relaxed);
+            result.synthetic_command_tag = common::synthetic_command_tag(&sql, &result, row_count);
+        }
+        Ok(result)
+    }
+
+    // Splits the script for the connection's dialect and runs its statements in order.
+    // Statements after a failing one are skipped when `stop_on_error` is set. The whole
+    // script needs one confirmation on prod connections.
+    pub async fn execute_script(
+        &self,
+        id: String,
+        sql: String,
+        stop_on_error: bool,
+        confirm_token: Option<String>,
+    ) -> Result<ScriptResult> {
+        if let Some(confirmation) = self.require_confirmation(&id, &sql, confirm_token).await {
+            return Ok(ScriptResult {
+                statements: Vec::new(),
+                confirmation: Some(confirmation),
+            });
+        }
+
+        let dialect = match self.configs.read().await.get(&id) {
+            Some(config) => Dialect::from(&config.driver),
+            None => Dialect::Generic,
+        };
+        let statements: Vec<String> = common::split_statements(&sql, dialect)
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        // Part of the script may have run before the connection dropped, so it is
+        // never retried
+        let mut statements = self
+            .with_service(&id, false, |service| {
+                let statements = statements.clone();
+                async move {
+                    let service = service.read().await;
+                    service.execute_script(statements, stop_on_error).await
+                }
+            })
+            .await?;
+        if self.has_command_tags(&id).await {
+            for statement in &mut statements {
+                if statement.status == StatementStatus::Success {
+                    let row_count = statement.result.rows.len();
+                    statement.result.synthetic_command_tag =
+                        common::synthetic_command_tag(&statement.sql, &statement.result, row_count);
+                }
+            }
+        }
+
+        Ok(ScriptResult {
+            statements,
+            confirmation: None,
+        })
+    }
+
+    // Command tags are rebuilt in the Postgres wording, which would be made up for the
+    // other databases
+    async fn has_command_tags(&self, id: &str) -> bool {
+        matches!(
+            self.configs.read().await.get(id),
+            Some(config) if config.driver == DatabaseDriver::Postgres
+        )
+    }
+
+    // The confirmation to hand back instead of running the SQL, when it is destructive,
+    // the connection is prod and no valid token came with it
+    async fn require_confirmation(
+        &self,
+        id: &str,
+        sql: &str,
+        confirm_token: Option<String>,
+    ) -> Option<ConfirmationRequest> {
+        let dialect = match self.configs.read().await.get(id) {
+            Some(config) if config.environment == Some(Environment::Prod) => {
+                Dialect::from(&config.driver)
+            }
+            _ => return None,
+        };
+        let reasons = common::destructive_reasons(sql, dialect);
+        if reasons.is_empty() || self.take_confirmation(id, sql, confirm_token) {
+            return None;
+        }
+
+        let token = uuid::Uuid::new_v4().to_string();
+        let mut confirmations = self.confirmations.lock().unwrap();
+        confirmations.retain(|_, pending| pending.issued.elapsed() < CONFIRMATION_TTL);
+        confirmations.insert(
+            token.clone(),
+            PendingConfirmation {
+                connection_id: id.to_string(),
+                sql: sql.to_string(),
+                issued: Instant::now(),
+            },
+        );
+        Some(ConfirmationRequest { token, reasons })
+    }
+
+    // Tokens are single use, whether or not they match
+    fn take_confirmation(&self, id: &str, sql: &str, token: Option<String>) -> bool {
+        let Some(token) = token else {
+            return false;
+        };
+        self.confirmations
+            .lock()
+            .unwrap()
+            .remove(&token)
+            .is_some_and(|pending| {
+                pending.connection_id == id
+                    && pending.sql == sql
+                    && pending.issued.elapsed() < CONFIRMATION_TTL
+            })
     }
 
     pub async fn cancel_query(&self, id: String, query_id: String) -> Result<()> {
-        let service_lock = self.get_service_read(&id).await?;
+        // A query can't outlive its connection, so there is nothing to reconnect for
+        let service_lock = self.get_service(&id).await?;
         let service = service_lock.read().await;
         service.cancel_query(query_id).await
     }
 
     pub async fn get_tables(&self, id: String, db_name: Option<String>) -> Result<Vec<String>> {
-        let service_lock = self.get_service_read(&id).await?;
-        // get_tables takes &mut self (might switch db), so we need write lock
-        let mut service = service_lock.write().await;
-        service.get_tables(db_name).await
+        self.with_service(&id, true, |service| {
+            let db_name = db_name.clone();
+            async move {
+                // get_tables takes &mut self (might switch db), so we need write lock
+                let mut service = service.write().await;
+                service.get_tables(db_name).await
+            }
+        })
+        .await
     }
 
     pub async fn get_databases(&self, id: String) -> Result<Vec<String>> {
-        let service_lock = self.get_service_read(&id).await?;
-        // get_databases takes &self
-        let service = service_lock.read().await;
-        service.get_databases().await
+        self.with_service(&id, true, |service| async move {
+            // get_databases takes &self
+            let service = service.read().await;
+            service.get_databases().await
+        })
+        .await
     }
 
     pub async fn get_schema(&self, id: String, db_name: Option<String>) -> Result<DbSchema> {
-        let service_lock = self.get_service_read(&id).await?;
-        // get_schema takes &mut self
-        let mut service = service_lock.write().await;
-        service.get_schema(db_name).await
+        self.with_service(&id, true, |service| {
+            let db_name = db_name.clone();
+            async move {
+                // get_schema takes &mut self
+                let mut service = service.write().await;
+                service.get_schema(db_name).await
+            }
+        })
+        .await
     }
 
     pub async fn get_table_data(&self, id: String, req: DataRequest) -> Result<QueryResult> {
-        let service_lock = self.get_service_read(&id).await?;
-        // get_table_data takes &self
-        let service = service_lock.read().await;
-        service.get_table_data(req).await
+        self.with_service(&id, true, |service| {
+            let req = req.clone();
+            async move {
+                // get_table_data takes &self
+                let service = service.read().await;
+                service.get_table_data(req).await
+            }
+        })
+        .await
     }
 
     pub async fn set_active_database(&self, id: String, db_name: String) -> Result<()> {
-        let service_lock = self.get_service_read(&id).await?;
-        // set_active_database takes &mut self
-        let mut service = service_lock.write().await;
-        service.set_active_database(db_name).await
+        self.with_service(&id, true, |service| {
+            let db_name = db_name.clone();
+            async move {
+                // set_active_database takes &mut self
+                let mut service = service.write().await;
+                service.set_active_database(db_name).await
+            }
+        })
+        .await
     }
 
     pub async fn get_primary_keys(&self, id: String, table_name: String) -> Result<Vec<String>> {
-        let service_lock = self.get_service_read(&id).await?;
-        // get_primary_keys takes &self
-        let service = service_lock.read().await;
-        service.get_primary_keys(table_name).await
+        self.with_service(&id, true, |service| {
+            let table_name = table_name.clone();
+            async move {
+                // get_primary_keys takes &self
+                let service = service.read().await;
+                service.get_primary_keys(table_name).await
+            }
+        })
+        .await
     }
 
     pub async fn update_rows(&self, id: String, updates: Vec<RowUpdate>) -> Result<UpdateResult> {
-        let service_lock = self.get_service_read(&id).await?;
-        // update_rows takes &self
-        let service = service_lock.read().await;
-        service.update_rows(updates).await
+        let read_only = self.configs.read().await.get(&id).and_then(|c| c.read_only);
+        if read_only.unwrap_or(false) {
+            return Err(DbError::ReadOnly);
+        }
+        // Never replayed: a lost response doesn't mean the updates were not applied
+        self.with_service(&id, false, |service| {
+            let updates = updates.clone();
+            async move {
+                // update_rows takes &self
+                let service = service.read().await;
+                service.update_rows(updates).await
+            }
+        })
+        .await
     }
 
     pub async fn get_dashboard_metrics(&self, id: String) -> Result<DashboardMetrics> {
-        let service_lock = self.get_service_read(&id).await?;
-        // get_dashboard_metrics takes &self
-        let service = service_lock.read().await;
-        service.get_dashboard_metrics().await
+        self.with_service(&id, true, |service| async move {
+            // get_dashboard_metrics takes &self
+            let service = service.read().await;
+            service.get_dashboard_metrics().await
+        })
+        .await
     }
 }
 
+async fn ping(service: &dyn DatabaseService) -> bool {
+    matches!(
+        tokio::time::timeout(PING_TIMEOUT, service.ping()).await,
+        Ok(Ok(()))
+    )
+}
 
+// Forwarded connections reach the driver as 127.0.0.1, so a certificate issued for the
+// real host can never pass the hostname check
+fn reject_hostname_verification(config: &ConnectionConfig, via: &str) -> Result<()> {
+    if config.ssl_mode == Some(SslMode::VerifyFull) {
+        return Err(DbError::Config(format!(
+            "verify-full can't check the server hostname through {}, use verify-ca instead",
+            via
+        )));
+    }
+    Ok(())
+}
 
+impl Default for DatabaseManager {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::db::traits::MockDatabaseService;
+    use crate::models::StatementResult;
 
     #[tokio::test]
     async fn test_connect_success() {
@@ -224,6 +757,7 @@ mod tests {
             ssh_user: None,
             ssh_password: None,
             ssh_key_path: None,
+            ..Default::default()
         };
 
         let result = manager.connect("conn1".to_string(), config).await;
@@ -262,6 +796,7 @@ mod tests {
             ssh_user: None,
             ssh_password: None,
             ssh_key_path: None,
+            ..Default::default()
         };
 
         let _ = manager.connect("conn1".to_string(), config).await;
@@ -280,12 +815,7 @@ mod tests {
         mock_service.expect_execute()
             .with(mockall::predicate::eq("SELECT 1"), mockall::predicate::eq(None::<String>))
             .times(1)
-            .returning(|_, _| Ok(QueryResult {
-                rows: vec![],
-                columns: vec![],
-                error: None,
-                duration: 0.0,
-            }));
+            .returning(|_, _| Ok(QueryResult::empty()));
 
         mock_factory.expect_create()
             .return_once(move |_| Box::new(mock_service));
@@ -307,10 +837,412 @@ mod tests {
             ssh_user: None,
             ssh_password: None,
             ssh_key_path: None,
+            ..Default::default()
         };
 
         let _ = manager.connect("conn1".to_string(), config).await;
         let result = manager.execute("conn1".to_string(), "SELECT 1".to_string(), None).await;
         assert!(result.is_ok());
     }
+
+    fn failing_service() -> MockDatabaseService {
+        let mut service = MockDatabaseService::new();
+        service
+            .expect_connect()
+            .returning(|_| Ok("Connected".to_string()));
+        service
+            .expect_execute()
+            .returning(|_, _| Err(DbError::Connection("connection reset".to_string())));
+        service
+            .expect_ping()
+            .returning(|| Err(DbError::Connection("connection reset".to_string())));
+        service.expect_disconnect().returning(|| Ok(()));
+        service
+    }
+
+    fn sequence_factory(services: Vec<MockDatabaseService>) -> MockDatabaseServiceFactory {
+        let services = std::sync::Mutex::new(services);
+        let mut factory = MockDatabaseServiceFactory::new();
+        factory
+            .expect_create()
+            .returning(move |_| Box::new(services.lock().unwrap().remove(0)));
+        factory
+    }
+
+    fn record_events(manager: &DatabaseManager) -> Arc<std::sync::Mutex<Vec<ConnectionEvent>>> {
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let recorded = events.clone();
+        manager.set_event_handler(move |event| recorded.lock().unwrap().push(event));
+        events
+    }
+
+    fn test_config() -> ConnectionConfig {
+        ConnectionConfig {
+            id: "conn1".to_string(),
+            name: "Test Connection".to_string(),
+            driver: DatabaseDriver::Postgres,
+            host: "localhost".to_string(),
+            port: 5432,
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reconnects_dead_connection() {
+        let mut fresh = MockDatabaseService::new();
+        fresh
+            .expect_connect()
+            .returning(|_| Ok("Connected".to_string()));
+        fresh
+            .expect_execute()
+            .times(1)
+            .returning(|_, _| Ok(QueryResult::empty()));
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![
+            failing_service(),
+            fresh,
+        ])));
+        let events = record_events(&manager);
+        manager
+            .connect("conn1".to_string(), test_config())
+            .await
+            .unwrap();
+
+        // The failed statement is reported, the next one runs on the new connection
+        let err = manager
+            .execute("conn1".to_string(), "SELECT 1".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DbError::Connection(_)));
+        let result = manager
+            .execute("conn1".to_string(), "SELECT 1".to_string(), None)
+            .await;
+        assert!(result.is_ok(), "{:?}", result.err());
+
+        let states: Vec<_> = events.lock().unwrap().iter().map(|e| e.state).collect();
+        assert_eq!(
+            states,
+            vec![ConnectionState::Reconnecting, ConnectionState::Connected]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_statement_not_rerun_after_reconnect() {
+        let mut fresh = MockDatabaseService::new();
+        fresh
+            .expect_connect()
+            .returning(|_| Ok("Connected".to_string()));
+        fresh.expect_execute().never();
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![
+            failing_service(),
+            fresh,
+        ])));
+        manager
+            .connect("conn1".to_string(), test_config())
+            .await
+            .unwrap();
+
+        let sql = "INSERT INTO orders VALUES (1)";
+        let err = manager
+            .execute("conn1".to_string(), sql.to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DbError::Connection(_)));
+    }
+
+    #[tokio::test]
+    async fn test_statement_error_does_not_reconnect() {
+        let mut service = MockDatabaseService::new();
+        service
+            .expect_connect()
+            .returning(|_| Ok("Connected".to_string()));
+        service
+            .expect_execute()
+            .times(1)
+            .returning(|_, _| Err(DbError::Query("syntax error".to_string())));
+        service.expect_ping().returning(|| Ok(()));
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        let events = record_events(&manager);
+        manager
+            .connect("conn1".to_string(), test_config())
+            .await
+            .unwrap();
+
+        let err = manager
+            .execute("conn1".to_string(), "SELEC 1".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DbError::Query(_)));
+        assert!(events.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_gives_up_after_bounded_attempts() {
+        let mut services = vec![failing_service()];
+        for _ in 0..MAX_RECONNECT_ATTEMPTS {
+            let mut service = MockDatabaseService::new();
+            service
+                .expect_connect()
+                .returning(|_| Err(DbError::Connection("connection refused".to_string())));
+            services.push(service);
+        }
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(services)));
+        let events = record_events(&manager);
+        manager
+            .connect("conn1".to_string(), test_config())
+            .await
+            .unwrap();
+
+        let err = manager
+            .execute("conn1".to_string(), "SELECT 1".to_string(), None)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("connection refused"));
+
+        let events = events.lock().unwrap();
+        assert_eq!(events.len(), MAX_RECONNECT_ATTEMPTS as usize + 1);
+        let last = events.last().unwrap();
+        assert_eq!(last.state, ConnectionState::Failed);
+        assert_eq!(
+            last.error.as_deref(),
+            Some("Connection error: connection refused")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_rows_rejected_when_read_only() {
+        let mut service = MockDatabaseService::new();
+        service.expect_connect().returning(|_| Ok("Connected".to_string()));
+        service.expect_update_rows().never();
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        let config = ConnectionConfig {
+            read_only: Some(true),
+            ..test_config()
+        };
+        manager.connect("conn1".to_string(), config).await.unwrap();
+
+        let err = manager
+            .update_rows("conn1".to_string(), vec![])
+            .await
+            .unwrap_err();
+        assert!(matches!(err, DbError::ReadOnly));
+    }
+
+    #[tokio::test]
+    async fn test_destructive_statement_needs_confirmation_on_prod() {
+        let mut service = MockDatabaseService::new();
+        service.expect_connect().returning(|_| Ok("Connected".to_string()));
+        service
+            .expect_execute()
+            .withf(|sql, _| sql == "DROP TABLE users")
+            .times(1)
+            .returning(|_, _| Ok(QueryResult::empty()));
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        let config = ConnectionConfig {
+            environment: Some(Environment::Prod),
+            ..test_config()
+        };
+        manager.connect("conn1".to_string(), config).await.unwrap();
+
+        let sql = "DROP TABLE users".to_string();
+        let held = manager
+            .execute("conn1".to_string(), sql.clone(), None)
+            .await
+            .unwrap();
+        let confirmation = held.confirmation.expect("No confirmation requested");
+        assert_eq!(confirmation.reasons, vec!["DROP"]);
+
+        // A token only confirms the statement it was issued for, and only once
+        let other = manager
+            .execute_confirmed(
+                "conn1".to_string(),
+                "DROP TABLE orders".to_string(),
+                None,
+                Some(confirmation.token.clone()),
+            )
+            .await
+            .unwrap();
+        assert!(other.confirmation.is_some());
+
+        let held = manager
+            .execute("conn1".to_string(), sql.clone(), None)
+            .await
+            .unwrap();
+        let token = held.confirmation.unwrap().token;
+        let result = manager
+            .execute_confirmed("conn1".to_string(), sql.clone(), None, Some(token.clone()))
+            .await
+            .unwrap();
+        assert!(result.confirmation.is_none());
+
+        let replayed = manager
+            .execute_confirmed("conn1".to_string(), sql, None, Some(token))
+            .await
+            .unwrap();
+        assert!(replayed.confirmation.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_confirmation_uses_connection_dialect() {
+        let mut service = MockDatabaseService::new();
+        service.expect_connect().returning(|_| Ok("Connected".to_string()));
+        service.expect_execute_script().never();
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        let config = ConnectionConfig {
+            environment: Some(Environment::Prod),
+            ..test_config()
+        };
+        manager.connect("conn1".to_string(), config).await.unwrap();
+
+        // Postgres runs the DROP as a statement of its own, whatever the backslash
+        let result = manager
+            .execute_script(
+                "conn1".to_string(),
+                r"SELECT 'C:\'; DROP TABLE users; SELECT 'x'".to_string(),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(result.confirmation.unwrap().reasons, vec!["DROP"]);
+    }
+
+    #[tokio::test]
+    async fn test_execute_script_splits_for_dialect() {
+        let mut service = MockDatabaseService::new();
+        service.expect_connect().returning(|_| Ok("Connected".to_string()));
+        service
+            .expect_execute_script()
+            .withf(|statements, stop_on_error| {
+                statements == &["SELECT $$a;b$$", "SELECT 2"] && *stop_on_error
+            })
+            .times(1)
+            .returning(|statements, _| {
+                Ok(statements
+                    .into_iter()
+                    .map(|sql| StatementResult::new(sql, QueryResult::empty()))
+                    .collect())
+            });
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        manager
+            .connect("conn1".to_string(), test_config())
+            .await
+            .unwrap();
+
+        let result = manager
+            .execute_script(
+                "conn1".to_string(),
+                "SELECT $$a;b$$; -- done;\nSELECT 2;".to_string(),
+                true,
+                None,
+            )
+            .await
+            .unwrap();
+        assert!(result.confirmation.is_none());
+        assert_eq!(result.statements.len(), 2);
+        assert_eq!(result.statements[0].status, StatementStatus::Success);
+        assert_eq!(
+            result.statements[1].result.synthetic_command_tag.as_deref(),
+            Some("SELECT 0")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_execute_stream_counts_streamed_rows() {
+        let mut service = MockDatabaseService::new();
+        service.expect_connect().returning(|_| Ok("Connected".to_string()));
+        service
+            .expect_execute_stream()
+            .withf(|sql, _, max_rows, _| sql == "SELECT n FROM t" && *max_rows == 3)
+            .times(1)
+            .returning(|_, _, _, sink| {
+                sink(RowBatch {
+                    columns: None,
+                    rows: vec![vec![serde_json::json!(1)], vec![serde_json::json!(2)]],
+                });
+                sink(RowBatch {
+                    columns: None,
+                    rows: vec![vec![serde_json::json!(3)]],
+                });
+                Ok(QueryResult {
+                    truncated: true,
+                    ..QueryResult::empty()
+                })
+            });
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        manager
+            .connect("conn1".to_string(), test_config())
+            .await
+            .unwrap();
+
+        let batches = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = batches.clone();
+        let sink: BatchSink = Arc::new(move |batch| received.lock().unwrap().push(batch));
+        let result = manager
+            .execute_stream(
+                "conn1".to_string(),
+                "SELECT n FROM t".to_string(),
+                None,
+                None,
+                3,
+                sink,
+            )
+            .await
+            .unwrap();
+        assert!(result.truncated);
+        assert_eq!(result.synthetic_command_tag.as_deref(), Some("SELECT 3"));
+        assert_eq!(batches.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_command_tags_only_for_postgres() {
+        let mut service = MockDatabaseService::new();
+        service.expect_connect().returning(|_| Ok("Connected".to_string()));
+        service.expect_execute().returning(|_, _| {
+            Ok(QueryResult {
+                rows_affected: Some(2),
+                ..QueryResult::empty()
+            })
+        });
+
+        let manager = DatabaseManager::new_with_factory(Box::new(sequence_factory(vec![service])));
+        let config = ConnectionConfig {
+            driver: DatabaseDriver::Mysql,
+            ..test_config()
+        };
+        manager.connect("conn1".to_string(), config).await.unwrap();
+
+        let result = manager
+            .execute("conn1".to_string(), "UPDATE t SET a = 1".to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(result.rows_affected, Some(2));
+        assert_eq!(result.synthetic_command_tag, None);
+    }
+
+    #[tokio::test]
+    async fn test_verify_full_rejected_through_proxy() {
+        let manager =
+            DatabaseManager::new_with_factory(Box::new(MockDatabaseServiceFactory::new()));
+        let config = ConnectionConfig {
+            ssl_mode: Some(SslMode::VerifyFull),
+            use_proxy: Some(true),
+            proxy_host: Some("proxy.internal".to_string()),
+            ..test_config()
+        };
+
+        let err = manager
+            .connect("conn1".to_string(), config)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("verify-ca"));
+    }
 }
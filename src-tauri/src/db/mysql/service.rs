@@ -1,13 +1,20 @@
-use crate::db::common::{build_select_sql, build_update_sql, QuoteStyle};
+use crate::db::common::{
+    build_select_sql, build_update_sql, column_info, init_statements, pool_options,
+    session_variables, statement_timeout, with_query_id, BatchSink, QuoteStyle, RowStream,
+};
 use crate::db::traits::DatabaseService;
 use crate::error::{DbError, Result};
 use crate::models::{
-    ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
-    UpdateResult,
+    ColumnInfo, ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
+    SslMode, StatementResult, UpdateResult,
 };
 use async_trait::async_trait;
-use sqlx::mysql::{MySqlConnectOptions, MySqlPoolOptions};
-use sqlx::{Column, MySql, Pool, Row, TypeInfo};
+use futures_util::TryStreamExt;
+use sqlx::mysql::{
+    MySqlColumn, MySqlConnectOptions, MySqlConnection, MySqlPoolOptions, MySqlQueryResult,
+    MySqlRow, MySqlSslMode,
+};
+use sqlx::{Column, Describe, Either, Executor, MySql, Pool, Row, TypeInfo};
 use std::collections::HashMap;
 use std::time::Instant;
 
@@ -28,11 +35,34 @@ impl MysqlService {
         self.pool.as_ref().ok_or(DbError::NotConnected)
     }
 
-    fn map_row(&self, row: &sqlx::mysql::MySqlRow) -> HashMap<String, serde_json::Value> {
-        let mut row_map = HashMap::new();
+    fn apply_tls(mut options: MySqlConnectOptions, config: &ConnectionConfig) -> MySqlConnectOptions {
+        let mode = match config.ssl_mode.unwrap_or_default() {
+            SslMode::Disable => MySqlSslMode::Disabled,
+            SslMode::Prefer => MySqlSslMode::Preferred,
+            SslMode::Require => MySqlSslMode::Required,
+            SslMode::VerifyCa => MySqlSslMode::VerifyCa,
+            SslMode::VerifyFull => MySqlSslMode::VerifyIdentity,
+        };
+        options = options.ssl_mode(mode);
+
+        if let Some(ca) = config.ssl_ca_path.as_deref().filter(|p| !p.is_empty()) {
+            options = options.ssl_ca(ca);
+        }
+        if let Some(cert) = config.ssl_cert_path.as_deref().filter(|p| !p.is_empty()) {
+            options = options.ssl_client_cert(cert);
+        }
+        if let Some(key) = config.ssl_key_path.as_deref().filter(|p| !p.is_empty()) {
+            options = options.ssl_client_key(key);
+        }
+
+        options
+    }
+
+    // Values in column order, so repeated column names each keep their own value
+    fn map_row(&self, row: &sqlx::mysql::MySqlRow) -> Vec<serde_json::Value> {
+        let mut values = Vec::with_capacity(row.columns().len());
 
         for col in row.columns() {
-            let name = col.name().to_string();
             let type_info = col.type_info().name();
 
             let value: serde_json::Value = match type_info {
@@ -134,10 +164,90 @@ impl MysqlService {
                     .map(serde_json::Value::String)
                     .unwrap_or(serde_json::Value::Null),
             };
-            row_map.insert(name, value);
+            values.push(value);
+        }
+
+        values
+    }
+
+    // Runs one statement on the given connection, with the rows it changed
+    async fn run_statement(&self, conn: &mut MySqlConnection, sql: &str) -> QueryResult {
+        let start = Instant::now();
+        let mut rows = Vec::new();
+        let fetched = fetch_rows(&mut *conn, sql, |row| {
+            rows.push(row);
+            true
+        })
+        .await;
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+        let done = match fetched {
+            Ok(done) => done,
+            Err(e) => return QueryResult::with_error(e.to_string(), duration),
+        };
+
+        // Described even without rows, so an empty result keeps its headers
+        let describe = conn.describe(sql).await.ok();
+        let columns = match (rows.first(), &describe) {
+            (Some(row), _) => column_infos(row.columns(), describe.as_ref()),
+            (None, Some(desc)) => column_infos(desc.columns(), describe.as_ref()),
+            (None, None) => Vec::new(),
+        };
+
+        QueryResult {
+            rows: rows.iter().map(|row| self.map_row(row)).collect(),
+            columns,
+            duration,
+            rows_affected: Some(done.rows_affected()),
+            // Zero when the statement generated no AUTO_INCREMENT value
+            last_insert_id: Some(done.last_insert_id()).filter(|id| *id > 0),
+            ..QueryResult::empty()
         }
+    }
+}
 
-        row_map
+// Hands the rows of every result set to `on_row` as they arrive, until it returns false,
+// and sums up the outcome of the statements. The SQL goes over the text protocol, since
+// MySQL refuses to prepare CREATE PROCEDURE, CREATE TRIGGER and the like.
+async fn fetch_rows(
+    conn: &mut MySqlConnection,
+    sql: &str,
+    mut on_row: impl FnMut(MySqlRow) -> bool,
+) -> sqlx::Result<MySqlQueryResult> {
+    let mut stream = conn.fetch_many(sql);
+    let mut outcome = MySqlQueryResult::default();
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            Either::Left(done) => outcome.extend(Some(done)),
+            Either::Right(row) => {
+                if !on_row(row) {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+// MySQL reports NOT NULL only when the statement is described, and sqlx doesn't
+// expose which table a column comes from
+fn column_infos(columns: &[MySqlColumn], describe: Option<&Describe<MySql>>) -> Vec<ColumnInfo> {
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| ColumnInfo {
+            nullable: describe.and_then(|d| d.nullable(i)),
+            ..column_info(col.name(), col.type_info().name())
+        })
+        .collect()
+}
+
+// Numbers stay bare since integer system variables refuse string values
+fn mysql_value(value: &str) -> String {
+    if value.parse::<f64>().is_ok_and(f64::is_finite) {
+        value.to_string()
+    } else {
+        format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''"))
     }
 }
 
@@ -157,15 +267,48 @@ impl DatabaseService for MysqlService {
             .port(config.port)
             .username(&config.user)
             .database(&config.database);
+        options = Self::apply_tls(options, config);
 
         if let Some(password) = &config.password {
             options = options.password(password);
         }
 
-        let pool = MySqlPoolOptions::new()
-            .max_connections(5)
-            .connect_with(options)
-            .await?;
+        let mut pool_options: MySqlPoolOptions = pool_options(config, 5);
+        let timeout = statement_timeout(config);
+        let read_only = config.read_only.unwrap_or(false);
+        let mut session_sql: Vec<String> = session_variables(config)?
+            .into_iter()
+            .map(|(name, value)| format!("SET SESSION {} = {}", name, mysql_value(&value)))
+            .collect();
+        session_sql.extend(init_statements(config));
+        if timeout.is_some() || read_only || !session_sql.is_empty() {
+            pool_options = pool_options.after_connect(move |conn, _| {
+                let session_sql = session_sql.clone();
+                Box::pin(async move {
+                    if let Some(timeout) = timeout {
+                        // MySQL limits SELECTs in milliseconds, MariaDB limits every
+                        // statement in seconds under a different name
+                        let mysql =
+                            format!("SET SESSION max_execution_time = {}", timeout.as_millis());
+                        if conn.execute(mysql.as_str()).await.is_err() {
+                            let mariadb =
+                                format!("SET SESSION max_statement_time = {}", timeout.as_secs());
+                            conn.execute(mariadb.as_str()).await?;
+                        }
+                    }
+                    for sql in &session_sql {
+                        conn.execute(sql.as_str()).await?;
+                    }
+                    // After the init script, which may still need to write
+                    if read_only {
+                        conn.execute("SET SESSION TRANSACTION READ ONLY").await?;
+                    }
+                    Ok(())
+                })
+            });
+        }
+
+        let pool = pool_options.connect_with(options).await?;
 
         self.pool = Some(pool);
         Ok("Connected to MySQL".to_string())
@@ -180,39 +323,97 @@ impl DatabaseService for MysqlService {
     }
 
     async fn execute(&self, sql: &str, query_id: Option<String>) -> Result<QueryResult> {
-        let pool = self.pool()?;
-        let start = Instant::now();
+        let final_sql = with_query_id(sql, query_id);
+        let mut conn = self.pool()?.acquire().await?;
+        Ok(self.run_statement(&mut conn, &final_sql).await)
+    }
 
-        let final_sql = if let Some(qid) = query_id {
-            format!("/* query_id: {} */ {}", qid, sql)
-        } else {
-            sql.to_string()
-        };
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult> {
+        let pool = self.pool()?;
+        let final_sql = with_query_id(sql, query_id);
+        let mut conn = pool.acquire().await?;
 
-        let result = sqlx::query(&final_sql).fetch_all(pool).await;
+        let start = Instant::now();
+        let mut rows = RowStream::new(sink, max_rows);
+        let mut columns = Vec::new();
+        let fetched = fetch_rows(&mut conn, &final_sql, |row| {
+            if rows.needs_columns() {
+                columns = row.columns().to_vec();
+                rows.set_columns(
+                    columns
+                        .iter()
+                        .map(|col| column_info(col.name(), col.type_info().name()))
+                        .collect(),
+                );
+            }
+            rows.push(self.map_row(&row))
+        })
+        .await;
+        let truncated = rows.finish();
         let duration = start.elapsed().as_secs_f64() * 1000.0;
 
-        match result {
-            Ok(rows) => {
-                let mut columns = Vec::new();
-
-                if !rows.is_empty() {
-                    for col in rows[0].columns() {
-                        columns.push(col.name().to_string());
-                    }
-                }
+        let done = match fetched {
+            Ok(done) => done,
+            Err(e) => return Ok(QueryResult::with_error(e.to_string(), duration)),
+        };
 
-                let data: Vec<_> = rows.iter().map(|row| self.map_row(row)).collect();
+        let describe = if truncated {
+            // The rest of the rows are still on their way. Dropping the connection ends
+            // the query, where draining it would read them all.
+            let _ = conn.close().await;
+            pool.describe(&final_sql).await.ok()
+        } else {
+            conn.describe(&final_sql).await.ok()
+        };
+        if columns.is_empty() {
+            if let Some(desc) = &describe {
+                columns = desc.columns().to_vec();
+            }
+        }
+        let columns = column_infos(&columns, describe.as_ref());
+
+        Ok(QueryResult {
+            columns,
+            duration,
+            rows_affected: (!truncated).then(|| done.rows_affected()),
+            last_insert_id: Some(done.last_insert_id()).filter(|id| *id > 0),
+            truncated,
+            ..QueryResult::empty()
+        })
+    }
 
-                Ok(QueryResult {
-                    rows: data,
-                    columns,
-                    error: None,
-                    duration,
-                })
+    async fn execute_script(
+        &self,
+        statements: Vec<String>,
+        stop_on_error: bool,
+    ) -> Result<Vec<StatementResult>> {
+        // One connection for the whole script, so session settings, temporary tables
+        // and transactions carry over from one statement to the next
+        let mut conn = self.pool()?.acquire().await?;
+        let mut results = Vec::with_capacity(statements.len());
+        let mut failed = false;
+        for sql in statements {
+            if failed {
+                results.push(StatementResult::skipped(sql));
+                continue;
             }
-            Err(e) => Ok(QueryResult::with_error(e.to_string(), duration)),
+            let result = self.run_statement(&mut conn, &sql).await;
+            failed = stop_on_error && result.error.is_some();
+            results.push(StatementResult::new(sql, result));
+        }
+
+        // A transaction the script left open would go back to the pool with the
+        // connection. ROLLBACK does nothing outside of one.
+        if conn.execute("ROLLBACK").await.is_err() {
+            let _ = conn.close().await;
         }
+        Ok(results)
     }
 
     async fn cancel_query(&self, query_id: String) -> Result<()> {
@@ -425,6 +626,32 @@ impl DatabaseService for MysqlService {
             table_count: table_count as i32,
             cache_hit_ratio: 0.0,
             top_queries: Vec::new(),
+            extra: HashMap::new(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_tls_maps_ssl_mode() {
+        // The sqlx modes don't implement PartialEq
+        for (mode, expected) in [
+            (None, "Preferred"),
+            (Some(SslMode::Disable), "Disabled"),
+            (Some(SslMode::Prefer), "Preferred"),
+            (Some(SslMode::Require), "Required"),
+            (Some(SslMode::VerifyCa), "VerifyCa"),
+            (Some(SslMode::VerifyFull), "VerifyIdentity"),
+        ] {
+            let config = ConnectionConfig {
+                ssl_mode: mode,
+                ..Default::default()
+            };
+            let options = MysqlService::apply_tls(MySqlConnectOptions::new(), &config);
+            assert_eq!(format!("{:?}", options.get_ssl_mode()), expected);
+        }
+    }
+}
@@ -1,13 +1,19 @@
-use crate::db::common::{build_select_sql, build_update_sql, QuoteStyle};
+use crate::db::common::{
+    build_select_sql, build_update_sql, column_info, init_statements, pool_options,
+    session_variables, statement_timeout, with_query_id, BatchSink, QuoteStyle, RowStream,
+};
 use crate::db::traits::DatabaseService;
 use crate::error::{DbError, Result};
 use crate::models::{
-    ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
-    UpdateResult,
+    ColumnInfo, ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
+    SslMode, StatementResult, UpdateResult,
 };
 use async_trait::async_trait;
-use sqlx::postgres::{PgConnectOptions, PgPoolOptions, PgSslMode};
-use sqlx::{Column, Executor, Pool, Postgres, Row, TypeInfo};
+use futures_util::TryStreamExt;
+use sqlx::postgres::{
+    PgColumn, PgConnectOptions, PgConnection, PgPoolOptions, PgQueryResult, PgRow, PgSslMode,
+};
+use sqlx::{Column, Describe, Either, Executor, Pool, Postgres, Row, TypeInfo};
 use std::collections::HashMap;
 use std::time::Instant;
 
@@ -28,11 +34,34 @@ impl PostgresService {
         self.pool.as_ref().ok_or(DbError::NotConnected)
     }
 
-    fn map_row(&self, row: &sqlx::postgres::PgRow) -> HashMap<String, serde_json::Value> {
-        let mut row_map = HashMap::new();
+    fn apply_tls(mut options: PgConnectOptions, config: &ConnectionConfig) -> PgConnectOptions {
+        let mode = match config.ssl_mode.unwrap_or_default() {
+            SslMode::Disable => PgSslMode::Disable,
+            SslMode::Prefer => PgSslMode::Prefer,
+            SslMode::Require => PgSslMode::Require,
+            SslMode::VerifyCa => PgSslMode::VerifyCa,
+            SslMode::VerifyFull => PgSslMode::VerifyFull,
+        };
+        options = options.ssl_mode(mode);
+
+        if let Some(ca) = config.ssl_ca_path.as_deref().filter(|p| !p.is_empty()) {
+            options = options.ssl_root_cert(ca);
+        }
+        if let Some(cert) = config.ssl_cert_path.as_deref().filter(|p| !p.is_empty()) {
+            options = options.ssl_client_cert(cert);
+        }
+        if let Some(key) = config.ssl_key_path.as_deref().filter(|p| !p.is_empty()) {
+            options = options.ssl_client_key(key);
+        }
+
+        options
+    }
+
+    // Values in column order, so repeated column names each keep their own value
+    fn map_row(&self, row: &sqlx::postgres::PgRow) -> Vec<serde_json::Value> {
+        let mut values = Vec::with_capacity(row.columns().len());
 
         for col in row.columns() {
-            let name = col.name().to_string();
             let type_info = col.type_info().name().to_lowercase();
 
             let value: serde_json::Value = match type_info.as_str() {
@@ -257,11 +286,128 @@ impl PostgresService {
                     .map(serde_json::Value::String)
                     .unwrap_or(serde_json::Value::Null),
             };
-            row_map.insert(name, value);
+            values.push(value);
         }
 
-        row_map
+        values
+    }
+
+    // Runs one statement on the given connection, with the rows it changed
+    async fn run_statement(&self, conn: &mut PgConnection, sql: &str) -> QueryResult {
+        let start = Instant::now();
+        let mut rows = Vec::new();
+        let fetched = fetch_rows(&mut *conn, sql, |row| {
+            rows.push(row);
+            true
+        })
+        .await;
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+        let done = match fetched {
+            Ok(done) => done,
+            Err(e) => return QueryResult::with_error(e.to_string(), duration),
+        };
+
+        let describe = conn.describe(sql).await.ok();
+        let columns = match (rows.first(), &describe) {
+            (Some(row), _) => row.columns().to_vec(),
+            (None, Some(desc)) => desc.columns().to_vec(),
+            (None, None) => Vec::new(),
+        };
+
+        QueryResult {
+            rows: rows.iter().map(|row| self.map_row(row)).collect(),
+            columns: column_infos(&mut *conn, &columns, describe.as_ref()).await,
+            duration,
+            rows_affected: Some(done.rows_affected()),
+            ..QueryResult::empty()
+        }
+    }
+}
+
+// Column metadata. For columns read straight from a table, the source table and column
+// are looked up in the catalog; lookup failures only leave them out. Nullability comes
+// from describing the statement, which accounts for outer joins, unlike the column's
+// NOT NULL flag.
+async fn column_infos<'c, E>(
+    executor: E,
+    columns: &[PgColumn],
+    describe: Option<&Describe<Postgres>>,
+) -> Vec<ColumnInfo>
+where
+    E: Executor<'c, Database = Postgres>,
+{
+    let mut infos: Vec<ColumnInfo> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| ColumnInfo {
+            nullable: describe.and_then(|d| d.nullable(i)),
+            ..column_info(col.name(), col.type_info().name())
+        })
+        .collect();
+
+    let relations: Vec<i64> = columns
+        .iter()
+        .filter_map(|col| col.relation_id())
+        .map(|oid| oid.0 as i64)
+        .collect();
+    if relations.is_empty() {
+        return infos;
+    }
+
+    let sql = "
+        SELECT a.attrelid::int8 AS relid, a.attnum, n.nspname, c.relname, a.attname
+        FROM pg_attribute a
+        JOIN pg_class c ON c.oid = a.attrelid
+        JOIN pg_namespace n ON n.oid = c.relnamespace
+        WHERE a.attrelid::int8 = ANY($1) AND a.attnum > 0
+    ";
+    let Ok(rows) = sqlx::query(sql).bind(relations).fetch_all(executor).await else {
+        return infos;
+    };
+
+    for (info, col) in infos.iter_mut().zip(columns) {
+        let (Some(relid), Some(attnum)) = (col.relation_id(), col.relation_attribute_no()) else {
+            continue;
+        };
+        let Some(row) = rows.iter().find(|r| {
+            r.get::<i64, _>("relid") == relid.0 as i64 && r.get::<i16, _>("attnum") == attnum
+        }) else {
+            continue;
+        };
+
+        let schema: String = row.get("nspname");
+        let table: String = row.get("relname");
+        info.source_table = Some(if schema == "public" {
+            table
+        } else {
+            format!("{}.{}", schema, table)
+        });
+        info.source_column = Some(row.get("attname"));
+    }
+    infos
+}
+
+// Hands the rows of every result set to `on_row` as they arrive, until it returns false,
+// and sums up the outcome of the statements
+async fn fetch_rows(
+    conn: &mut PgConnection,
+    sql: &str,
+    mut on_row: impl FnMut(PgRow) -> bool,
+) -> sqlx::Result<PgQueryResult> {
+    let mut stream = conn.fetch_many(sqlx::query(sql));
+    let mut outcome = PgQueryResult::default();
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            Either::Left(done) => outcome.extend(Some(done)),
+            Either::Right(row) => {
+                if !on_row(row) {
+                    break;
+                }
+            }
+        }
     }
+    Ok(outcome)
 }
 
 impl Default for PostgresService {
@@ -279,17 +425,49 @@ impl DatabaseService for PostgresService {
             .host(&config.host)
             .port(config.port)
             .username(&config.user)
-            .database(&config.database)
-            .ssl_mode(PgSslMode::Prefer);
+            .database(&config.database);
+        options = Self::apply_tls(options, config);
 
         if let Some(password) = &config.password {
             options = options.password(password);
         }
+        if let Some(name) = &config.application_name {
+            options = options.application_name(name);
+        }
 
-        let pool = PgPoolOptions::new()
-            .max_connections(20)
-            .connect_with(options)
-            .await?;
+        // Set per session rather than as startup parameters, which pgbouncer rejects
+        let mut session_sql = Vec::new();
+        if let Some(timeout) = statement_timeout(config) {
+            session_sql.push(format!("SET statement_timeout = {}", timeout.as_millis()));
+        }
+        for (name, value) in session_variables(config)? {
+            // set_config takes list values like search_path as one string, SET doesn't
+            session_sql.push(format!(
+                "SELECT set_config('{}', '{}', false)",
+                name,
+                value.replace('\'', "''")
+            ));
+        }
+        session_sql.extend(init_statements(config));
+        // After the init script, which may still need to write
+        if config.read_only.unwrap_or(false) {
+            session_sql.push("SET default_transaction_read_only = on".to_string());
+        }
+
+        let mut pool_options: PgPoolOptions = pool_options(config, 20);
+        if !session_sql.is_empty() {
+            pool_options = pool_options.after_connect(move |conn, _| {
+                let session_sql = session_sql.clone();
+                Box::pin(async move {
+                    for sql in &session_sql {
+                        conn.execute(sql.as_str()).await?;
+                    }
+                    Ok(())
+                })
+            });
+        }
+
+        let pool = pool_options.connect_with(options).await?;
 
         self.pool = Some(pool);
         Ok("Connected to Postgres".to_string())
@@ -304,43 +482,104 @@ impl DatabaseService for PostgresService {
     }
 
     async fn execute(&self, sql: &str, query_id: Option<String>) -> Result<QueryResult> {
-        let pool = self.pool()?;
-        let start = Instant::now();
+        let final_sql = with_query_id(sql, query_id);
+        let mut conn = self.pool()?.acquire().await?;
+        Ok(self.run_statement(&mut conn, &final_sql).await)
+    }
 
-        let final_sql = if let Some(qid) = query_id {
-            format!("/* query_id: {} */ {}", qid, sql)
-        } else {
-            sql.to_string()
-        };
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult> {
+        let pool = self.pool()?;
+        let final_sql = with_query_id(sql, query_id);
+        let mut conn = pool.acquire().await?;
 
-        let result = sqlx::query(&final_sql).fetch_all(pool).await;
+        let start = Instant::now();
+        let mut rows = RowStream::new(sink, max_rows);
+        let mut columns = Vec::new();
+        let fetched = fetch_rows(&mut conn, &final_sql, |row| {
+            if rows.needs_columns() {
+                columns = row.columns().to_vec();
+                rows.set_columns(
+                    columns
+                        .iter()
+                        .map(|col| column_info(col.name(), col.type_info().name()))
+                        .collect(),
+                );
+            }
+            rows.push(self.map_row(&row))
+        })
+        .await;
+        let truncated = rows.finish();
         let duration = start.elapsed().as_secs_f64() * 1000.0;
 
-        match result {
-            Ok(rows) => {
-                let mut columns = Vec::new();
+        let done = match fetched {
+            Ok(done) => done,
+            Err(e) => return Ok(QueryResult::with_error(e.to_string(), duration)),
+        };
 
-                if !rows.is_empty() {
-                    for col in rows[0].columns() {
-                        columns.push(col.name().to_string());
-                    }
-                } else if let Ok(desc) = pool.describe(&final_sql).await {
-                    for col in desc.columns() {
-                        columns.push(col.name().to_string());
-                    }
+        let columns = if truncated {
+            // The rest of the rows are still on their way. Dropping the connection ends
+            // the query, where draining it would read them all.
+            let _ = conn.close().await;
+            let describe = pool.describe(&final_sql).await.ok();
+            column_infos(pool, &columns, describe.as_ref()).await
+        } else {
+            let describe = conn.describe(&final_sql).await.ok();
+            if columns.is_empty() {
+                if let Some(desc) = &describe {
+                    columns = desc.columns().to_vec();
                 }
+            }
+            column_infos(&mut *conn, &columns, describe.as_ref()).await
+        };
 
-                let data: Vec<_> = rows.iter().map(|row| self.map_row(row)).collect();
+        Ok(QueryResult {
+            columns,
+            duration,
+            rows_affected: (!truncated).then(|| done.rows_affected()),
+            truncated,
+            ..QueryResult::empty()
+        })
+    }
 
-                Ok(QueryResult {
-                    rows: data,
-                    columns,
-                    error: None,
-                    duration,
-                })
+    async fn execute_script(
+        &self,
+        statements: Vec<String>,
+        stop_on_error: bool,
+    ) -> Result<Vec<StatementResult>> {
+        // One connection for the whole script, so session settings, temporary tables
+        // and transactions carry over from one statement to the next
+        let mut conn = self.pool()?.acquire().await?;
+        let mut results = Vec::with_capacity(statements.len());
+        let mut failed = false;
+        for sql in statements {
+            if failed {
+                results.push(StatementResult::skipped(sql));
+                continue;
             }
-            Err(e) => Ok(QueryResult::with_error(e.to_string(), duration)),
+            let result = self.run_statement(&mut conn, &sql).await;
+            failed = stop_on_error && result.error.is_some();
+            results.push(StatementResult::new(sql, result));
         }
+
+        // A transaction the script left open would go back to the pool with the
+        // connection. The timestamps only differ after the first statement of a
+        // transaction block, and the check itself fails in an aborted one.
+        let open = sqlx::query_scalar::<_, bool>(
+            "SELECT transaction_timestamp() <> statement_timestamp()",
+        )
+        .fetch_one(&mut *conn)
+        .await
+        .unwrap_or(true);
+        if open && conn.execute("ROLLBACK").await.is_err() {
+            let _ = conn.close().await;
+        }
+        Ok(results)
     }
 
     async fn cancel_query(&self, query_id: String) -> Result<()> {
@@ -563,6 +802,32 @@ impl DatabaseService for PostgresService {
             table_count: table_count as i32,
             cache_hit_ratio: 0.99,
             top_queries: Vec::new(),
+            extra: HashMap::new(),
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply_tls_maps_ssl_mode() {
+        // The sqlx modes don't implement PartialEq
+        for (mode, expected) in [
+            (None, "Prefer"),
+            (Some(SslMode::Disable), "Disable"),
+            (Some(SslMode::Prefer), "Prefer"),
+            (Some(SslMode::Require), "Require"),
+            (Some(SslMode::VerifyCa), "VerifyCa"),
+            (Some(SslMode::VerifyFull), "VerifyFull"),
+        ] {
+            let config = ConnectionConfig {
+                ssl_mode: mode,
+                ..Default::default()
+            };
+            let options = PostgresService::apply_tls(PgConnectOptions::new(), &config);
+            assert_eq!(format!("{:?}", options.get_ssl_mode()), expected);
+        }
+    }
+}
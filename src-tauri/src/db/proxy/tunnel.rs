@@ -0,0 +1,143 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::net::{TcpListener, TcpStream};
+
+use super::ProxySettings;
+use crate::error::{DbError, Result};
+
+// Local forwarder for drivers that can't talk to a proxy themselves. Every accepted
+// connection gets its own connection through the proxy to the remote host.
+pub struct ProxyTunnelService {
+    stop_signal: Option<tokio::sync::oneshot::Sender<()>>,
+    last_error: Arc<Mutex<Option<String>>>,
+}
+
+impl ProxyTunnelService {
+    pub fn new() -> Self {
+        ProxyTunnelService {
+            stop_signal: None,
+            last_error: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    pub async fn create_tunnel(
+        &mut self,
+        proxy: &ProxySettings,
+        remote_host: &str,
+        remote_port: u16,
+        timeout: Option<Duration>,
+    ) -> Result<u16> {
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let local_port = listener.local_addr()?.port();
+
+        let (tx, mut rx) = tokio::sync::oneshot::channel::<()>();
+        self.stop_signal = Some(tx);
+
+        let proxy = proxy.clone();
+        let remote_host = remote_host.to_string();
+        let last_error = self.last_error.clone();
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut rx => {
+                        log::info!("Stopping proxy tunnel");
+                        break;
+                    }
+                    Ok((mut socket, _)) = listener.accept() => {
+                        let proxy = proxy.clone();
+                        let remote_host = remote_host.clone();
+                        let last_error = last_error.clone();
+                        tokio::spawn(async move {
+                            if let Err(e) =
+                                forward(&mut socket, proxy, remote_host, remote_port, timeout).await
+                            {
+                                log::error!("Proxy tunnel error: {}", e);
+                                // Recorded before the socket is closed, so the driver's
+                                // connect error can be replaced with it
+                                *last_error.lock().unwrap() = Some(e.to_string());
+                            }
+                        });
+                    }
+                }
+            }
+        });
+
+        Ok(local_port)
+    }
+
+    pub fn last_error(&self) -> Option<String> {
+        self.last_error.lock().unwrap().clone()
+    }
+
+    pub fn close(&mut self) {
+        if let Some(tx) = self.stop_signal.take() {
+            let _ = tx.send(());
+        }
+    }
+}
+
+impl Default for ProxyTunnelService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+async fn forward(
+    socket: &mut TcpStream,
+    proxy: ProxySettings,
+    remote_host: String,
+    remote_port: u16,
+    timeout: Option<Duration>,
+) -> Result<()> {
+    let upstream =
+        tokio::task::spawn_blocking(move || proxy.connect(&remote_host, remote_port, timeout))
+            .await
+            .map_err(|e| DbError::Proxy(e.to_string()))??;
+    upstream.set_nonblocking(true)?;
+    let mut upstream = TcpStream::from_std(upstream)?;
+    let _ = upstream.set_nodelay(true);
+    let _ = socket.set_nodelay(true);
+
+    // Resets after the handshake are ordinary disconnects, not proxy failures
+    let _ = tokio::io::copy_bidirectional(socket, &mut upstream).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ProxyType;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn test_tunnel_records_proxy_failure() {
+        // Nothing listens on a port that was just released
+        let port = {
+            let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap().port()
+        };
+        let proxy = ProxySettings {
+            kind: ProxyType::Socks5,
+            host: "127.0.0.1".to_string(),
+            port,
+            user: None,
+            password: None,
+        };
+
+        let mut tunnel = ProxyTunnelService::new();
+        let local_port = tunnel
+            .create_tunnel(&proxy, "db.local", 5432, Some(Duration::from_secs(2)))
+            .await
+            .unwrap();
+
+        let mut socket = TcpStream::connect(("127.0.0.1", local_port)).await.unwrap();
+        let _ = socket.write_all(b"ping").await;
+        let mut buf = [0u8; 4];
+        assert_eq!(socket.read(&mut buf).await.unwrap_or(0), 0);
+
+        let error = tunnel.last_error().expect("No proxy error recorded");
+        assert!(error.contains("Failed to connect to proxy"), "{}", error);
+        tunnel.close();
+    }
+}
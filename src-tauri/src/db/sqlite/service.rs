@@ -0,0 +1,904 @@
+use crate::db::common::{
+    build_qualified_update_sql, column_info, init_statements, pool_options, session_variables,
+    setting_literal, validate_identifier, BatchSink, QuoteStyle, RowStream,
+};
+use crate::db::traits::DatabaseService;
+use crate::error::{DbError, Result};
+use crate::models::{
+    ColumnInfo, ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
+    StatementResult, UpdateResult,
+};
+use async_trait::async_trait;
+use futures_util::TryStreamExt;
+use sqlx::sqlite::{
+    SqliteConnectOptions, SqliteConnection, SqlitePoolOptions, SqliteQueryResult, SqliteRow,
+};
+use sqlx::{Column, Either, Executor, Pool, Row, Sqlite, TypeInfo, ValueRef};
+use std::collections::HashMap;
+use std::time::Instant;
+
+const DEFAULT_SCHEMA: &str = "main";
+
+pub struct SqliteService {
+    pool: Option<Pool<Sqlite>>,
+    last_config: Option<ConnectionConfig>,
+    active_schema: String,
+}
+
+impl SqliteService {
+    pub fn new() -> Self {
+        SqliteService {
+            pool: None,
+            last_config: None,
+            active_schema: DEFAULT_SCHEMA.to_string(),
+        }
+    }
+
+    fn pool(&self) -> Result<&Pool<Sqlite>> {
+        self.pool.as_ref().ok_or(DbError::NotConnected)
+    }
+
+    fn file_path(&self) -> Option<&str> {
+        self.last_config.as_ref().map(|c| c.database.as_str())
+    }
+
+    // Switches the schema (main, temp or an attached database) used for browsing.
+    fn select_schema(&mut self, db_name: Option<String>) -> Result<()> {
+        if let Some(db) = db_name {
+            validate_identifier(&db)?;
+            self.active_schema = db;
+        }
+        Ok(())
+    }
+
+    // Splits `schema.table`, with bare names belonging to the schema used for browsing
+    fn qualify(&self, table_name: &str) -> (String, String) {
+        match table_name.split_once('.') {
+            Some((schema, table)) => (schema.to_string(), table.to_string()),
+            None => (self.active_schema.clone(), table_name.to_string()),
+        }
+    }
+
+    // Values in column order, so repeated column names each keep their own value
+    fn map_row(&self, row: &sqlx::sqlite::SqliteRow) -> Vec<serde_json::Value> {
+        let mut values = Vec::with_capacity(row.columns().len());
+
+        for col in row.columns() {
+            let declared = col.type_info().name().to_uppercase();
+
+            // SQLite is dynamically typed, so decode by the storage class of the value itself
+            // and only use the declared column type to refine it (e.g. BOOLEAN stored as 0/1).
+            let storage = match row.try_get_raw(col.ordinal()) {
+                Ok(raw) if raw.is_null() => None,
+                Ok(raw) => Some(raw.type_info().name().to_string()),
+                Err(_) => None,
+            };
+
+            let value: serde_json::Value = match storage.as_deref() {
+                None => serde_json::Value::Null,
+                Some("INTEGER") if declared == "BOOLEAN" => row
+                    .try_get::<Option<bool>, _>(col.ordinal())
+                    .ok()
+                    .flatten()
+                    .map(serde_json::Value::Bool)
+                    .unwrap_or(serde_json::Value::Null),
+                Some("INTEGER") => row
+                    .try_get::<Option<i64>, _>(col.ordinal())
+                    .ok()
+                    .flatten()
+                    .map(|v| serde_json::Value::Number(v.into()))
+                    .unwrap_or(serde_json::Value::Null),
+                Some("REAL") => row
+                    .try_get::<Option<f64>, _>(col.ordinal())
+                    .ok()
+                    .flatten()
+                    .and_then(serde_json::Number::from_f64)
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null),
+                Some("BLOB") => row
+                    .try_get::<Option<Vec<u8>>, _>(col.ordinal())
+                    .ok()
+                    .flatten()
+                    .map(|bytes| {
+                        serde_json::Value::String(format!("(binary {} bytes)", bytes.len()))
+                    })
+                    .unwrap_or(serde_json::Value::Null),
+                _ => row
+                    .try_get::<Option<String>, _>(col.ordinal())
+                    .ok()
+                    .flatten()
+                    .map(serde_json::Value::String)
+                    .unwrap_or(serde_json::Value::Null),
+            };
+            values.push(value);
+        }
+
+        values
+    }
+
+    // Runs one statement on the given connection, with the rows it changed
+    async fn run_statement(&self, conn: &mut SqliteConnection, sql: &str) -> QueryResult {
+        let start = Instant::now();
+        let mut rows = Vec::new();
+        let fetched = fetch_rows(&mut *conn, sql, |row| {
+            rows.push(row);
+            true
+        })
+        .await;
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+        let done = match fetched {
+            Ok(done) => done,
+            Err(e) => return QueryResult::with_error(e.to_string(), duration),
+        };
+
+        let columns = column_infos(&mut *conn, sql, rows.first()).await;
+
+        QueryResult {
+            rows: rows.iter().map(|row| self.map_row(row)).collect(),
+            columns,
+            duration,
+            rows_affected: Some(done.rows_affected()),
+            ..QueryResult::empty()
+        }
+    }
+}
+
+// Hands the rows of every result set to `on_row` as they arrive, until it returns false,
+// and sums up the outcome of the statements
+async fn fetch_rows(
+    conn: &mut SqliteConnection,
+    sql: &str,
+    mut on_row: impl FnMut(SqliteRow) -> bool,
+) -> sqlx::Result<SqliteQueryResult> {
+    let mut stream = conn.fetch_many(sqlx::query(sql));
+    let mut outcome = SqliteQueryResult::default();
+    while let Some(item) = stream.try_next().await? {
+        match item {
+            Either::Left(done) => outcome.extend(Some(done)),
+            Either::Right(row) => {
+                if !on_row(row) {
+                    break;
+                }
+            }
+        }
+    }
+    Ok(outcome)
+}
+
+// Nullability comes from describing the statement, which also supplies the columns
+// when there are no rows to take them from
+async fn column_infos<'c, E>(executor: E, sql: &str, row: Option<&SqliteRow>) -> Vec<ColumnInfo>
+where
+    E: Executor<'c, Database = Sqlite>,
+{
+    let describe = executor.describe(sql).await.ok();
+    let columns = match (row, &describe) {
+        (Some(row), _) => row.columns(),
+        (None, Some(describe)) => describe.columns(),
+        (None, None) => &[],
+    };
+    columns
+        .iter()
+        .enumerate()
+        .map(|(i, col)| {
+            // Expressions have no declared type, so take the one of the first value
+            let type_name = match row.and_then(|row| row.try_get_raw(i).ok()) {
+                Some(value) if col.type_info().is_null() => value.type_info().name().to_string(),
+                _ => col.type_info().name().to_string(),
+            };
+            ColumnInfo {
+                nullable: describe.as_ref().and_then(|d| d.nullable(i)),
+                ..column_info(col.name(), &type_name)
+            }
+        })
+        .collect()
+}
+
+impl Default for SqliteService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl DatabaseService for SqliteService {
+    async fn connect(&mut self, config: &ConnectionConfig) -> Result<String> {
+        if config.database.trim().is_empty() {
+            return Err(DbError::Config(
+                "SQLite connections require a database file path".to_string(),
+            ));
+        }
+
+        self.last_config = Some(config.clone());
+        self.active_schema = DEFAULT_SCHEMA.to_string();
+
+        let options = SqliteConnectOptions::new()
+            .filename(&config.database)
+            .create_if_missing(false)
+            .read_only(config.read_only.unwrap_or(false));
+
+        // ATTACH and temp tables only exist on the connection that ran them, so by default
+        // everything goes through a single connection
+        let mut pool_options: SqlitePoolOptions = pool_options(config, 1);
+        let mut init_sql: Vec<String> = session_variables(config)?
+            .into_iter()
+            .map(|(name, value)| format!("PRAGMA {} = {}", name, setting_literal(&value)))
+            .collect();
+        init_sql.extend(init_statements(config));
+        if !init_sql.is_empty() {
+            pool_options = pool_options.after_connect(move |conn, _| {
+                let init_sql = init_sql.clone();
+                Box::pin(async move {
+                    for sql in &init_sql {
+                        conn.execute(sql.as_str()).await?;
+                    }
+                    Ok(())
+                })
+            });
+        }
+        let pool = pool_options.connect_with(options).await?;
+
+        self.pool = Some(pool);
+        Ok("Connected to SQLite".to_string())
+    }
+
+    async fn disconnect(&mut self) -> Result<()> {
+        if let Some(pool) = &self.pool {
+            pool.close().await;
+        }
+        self.pool = None;
+        Ok(())
+    }
+
+    async fn execute(&self, sql: &str, _query_id: Option<String>) -> Result<QueryResult> {
+        let mut conn = self.pool()?.acquire().await?;
+        Ok(self.run_statement(&mut conn, sql).await)
+    }
+
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        _query_id: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult> {
+        let pool = self.pool()?;
+        let mut conn = pool.acquire().await?;
+
+        let start = Instant::now();
+        let mut rows = RowStream::new(sink, max_rows);
+        let mut first_row = None;
+        let fetched = fetch_rows(&mut conn, sql, |row| {
+            if rows.needs_columns() {
+                rows.set_columns(
+                    row.columns()
+                        .iter()
+                        .map(|col| column_info(col.name(), col.type_info().name()))
+                        .collect(),
+                );
+            }
+            let more = rows.push(self.map_row(&row));
+            if first_row.is_none() {
+                first_row = Some(row);
+            }
+            more
+        })
+        .await;
+        let truncated = rows.finish();
+        let duration = start.elapsed().as_secs_f64() * 1000.0;
+
+        let done = match fetched {
+            Ok(done) => done,
+            Err(e) => return Ok(QueryResult::with_error(e.to_string(), duration)),
+        };
+
+        let columns = if truncated {
+            // Leaves the statement unfinished, so the connection isn't reused
+            let _ = conn.close().await;
+            column_infos(pool, sql, first_row.as_ref()).await
+        } else {
+            column_infos(&mut *conn, sql, first_row.as_ref()).await
+        };
+
+        Ok(QueryResult {
+            columns,
+            duration,
+            rows_affected: (!truncated).then(|| done.rows_affected()),
+            truncated,
+            ..QueryResult::empty()
+        })
+    }
+
+    async fn execute_script(
+        &self,
+        statements: Vec<String>,
+        stop_on_error: bool,
+    ) -> Result<Vec<StatementResult>> {
+        // One connection for the whole script, so session settings, temporary tables
+        // and transactions carry over from one statement to the next
+        let mut conn = self.pool()?.acquire().await?;
+        let mut results = Vec::with_capacity(statements.len());
+        let mut failed = false;
+        for sql in statements {
+            if failed {
+                results.push(StatementResult::skipped(sql));
+                continue;
+            }
+            let result = self.run_statement(&mut conn, &sql).await;
+            failed = stop_on_error && result.error.is_some();
+            results.push(StatementResult::new(sql, result));
+        }
+
+        // A transaction the script left open would keep the database locked. ROLLBACK
+        // only fails when there is none.
+        let _ = conn.execute("ROLLBACK").await;
+        Ok(results)
+    }
+
+    async fn cancel_query(&self, _query_id: String) -> Result<()> {
+        // SQLite runs in-process and has no server-side session to signal.
+        Err(DbError::Query(
+            "Cancelling queries is not supported for SQLite".to_string(),
+        ))
+    }
+
+    async fn get_tables(&mut self, db_name: Option<String>) -> Result<Vec<String>> {
+        self.select_schema(db_name)?;
+
+        let pool = self.pool()?;
+        let sql = format!(
+            "SELECT name FROM {}.sqlite_master WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%' ORDER BY name",
+            QuoteStyle::DoubleQuote.quote(&self.active_schema)
+        );
+        let rows = sqlx::query(&sql).fetch_all(pool).await?;
+
+        let tables: Vec<String> = rows.iter().map(|r| r.get::<String, _>("name")).collect();
+        Ok(tables)
+    }
+
+    async fn get_databases(&self) -> Result<Vec<String>> {
+        let pool = self.pool()?;
+        let rows = sqlx::query("SELECT name FROM pragma_database_list ORDER BY seq")
+            .fetch_all(pool)
+            .await?;
+        let dbs: Vec<String> = rows.iter().map(|r| r.get::<String, _>("name")).collect();
+        Ok(dbs)
+    }
+
+    async fn get_schema(&mut self, db_name: Option<String>) -> Result<DbSchema> {
+        self.select_schema(db_name)?;
+
+        let pool = self.pool()?;
+        let sql = format!(
+            "
+            SELECT m.name AS table_name, p.name AS column_name
+            FROM {schema}.sqlite_master m
+            JOIN pragma_table_info(m.name, '{schema_name}') p
+            WHERE m.type IN ('table', 'view') AND m.name NOT LIKE 'sqlite_%'
+            ORDER BY m.name, p.cid
+        ",
+            schema = QuoteStyle::DoubleQuote.quote(&self.active_schema),
+            schema_name = self.active_schema
+        );
+
+        let rows = sqlx::query(&sql).fetch_all(pool).await?;
+
+        let mut schema: DbSchema = HashMap::new();
+        for row in rows {
+            let table: String = row.get("table_name");
+            let column: String = row.get("column_name");
+            schema.entry(table).or_default().push(column);
+        }
+
+        Ok(schema)
+    }
+
+    async fn get_table_data(&self, req: DataRequest) -> Result<QueryResult> {
+        let (schema, table) = self.qualify(&req.table_name);
+        validate_identifier(&schema)?;
+        validate_identifier(&table)?;
+        let sql = format!(
+            "SELECT * FROM {}.{} LIMIT {} OFFSET {}",
+            QuoteStyle::DoubleQuote.quote(&schema),
+            QuoteStyle::DoubleQuote.quote(&table),
+            req.limit,
+            req.offset
+        );
+        self.execute(&sql, None).await
+    }
+
+    async fn set_active_database(&mut self, db_name: String) -> Result<()> {
+        let known = self.get_databases().await?;
+        if !known.contains(&db_name) {
+            return Err(DbError::Config(format!(
+                "Database {} is not attached",
+                db_name
+            )));
+        }
+        self.select_schema(Some(db_name))
+    }
+
+    async fn get_primary_keys(&self, table_name: String) -> Result<Vec<String>> {
+        let pool = self.pool()?;
+
+        let (schema, table) = self.qualify(&table_name);
+
+        let rows = sqlx::query("SELECT name FROM pragma_table_info(?1, ?2) WHERE pk > 0 ORDER BY pk")
+            .bind(table)
+            .bind(schema)
+            .fetch_all(pool)
+            .await?;
+
+        let keys: Vec<String> = rows.iter().map(|r| r.get::<String, _>("name")).collect();
+        Ok(keys)
+    }
+
+    async fn update_rows(&self, updates: Vec<RowUpdate>) -> Result<UpdateResult> {
+        let pool = self.pool()?;
+        let mut affected = 0;
+
+        for update in updates {
+            if update.changes.is_empty() {
+                continue;
+            }
+
+            let (schema, table) = self.qualify(&update.table_name);
+            let sql = build_qualified_update_sql(
+                &schema,
+                &table,
+                &update.changes,
+                &update.primary_keys,
+                QuoteStyle::DoubleQuote,
+            )?;
+
+            let res = sqlx::query(&sql).execute(pool).await?;
+            affected += res.rows_affected();
+        }
+
+        Ok(UpdateResult::success(affected))
+    }
+
+    async fn get_dashboard_metrics(&self) -> Result<DashboardMetrics> {
+        let pool = self.pool()?;
+
+        let version: String = sqlx::query("SELECT sqlite_version()")
+            .fetch_one(pool)
+            .await?
+            .get(0);
+
+        let page_count: i64 = sqlx::query("PRAGMA page_count")
+            .fetch_one(pool)
+            .await?
+            .get(0);
+
+        let page_size: i64 = sqlx::query("PRAGMA page_size")
+            .fetch_one(pool)
+            .await?
+            .get(0);
+
+        let journal_mode: String = sqlx::query("PRAGMA journal_mode")
+            .fetch_one(pool)
+            .await?
+            .get(0);
+
+        let table_count: i64 = sqlx::query(
+            "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+        )
+        .fetch_one(pool)
+        .await?
+        .get(0);
+
+        // Fall back to the page-based size for files we cannot stat (e.g. ":memory:").
+        let file_size = self
+            .file_path()
+            .and_then(|path| std::fs::metadata(path).ok())
+            .map(|meta| meta.len() as f64)
+            .unwrap_or((page_count * page_size) as f64);
+
+        let mut extra = HashMap::new();
+        extra.insert("pageCount".to_string(), page_count.to_string());
+        extra.insert("pageSize".to_string(), page_size.to_string());
+        extra.insert("journalMode".to_string(), journal_mode);
+
+        Ok(DashboardMetrics {
+            version,
+            uptime: 0,
+            active_connections: pool.size() as i32,
+            max_connections: pool.options().get_max_connections() as i32,
+            db_size: format!("{:.2} MB", file_size / 1024.0 / 1024.0),
+            indexes_size: "N/A".to_string(),
+            table_count: table_count as i32,
+            cache_hit_ratio: 0.0,
+            top_queries: Vec::new(),
+            extra,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{DatabaseDriver, LogicalType, SessionVariable, StatementStatus};
+    use serde_json::json;
+    use tempfile::tempdir;
+
+    async fn connected_service(path: &std::path::Path) -> SqliteService {
+        let options = SqliteConnectOptions::new()
+            .filename(path)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new().connect_with(options).await.unwrap();
+        sqlx::query("CREATE TABLE users (id INTEGER PRIMARY KEY, name TEXT, active BOOLEAN, score REAL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO users (name, active, score) VALUES ('Alice', 1, 1.5), ('Bob', 0, NULL)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let config = ConnectionConfig {
+            id: "sqlite".to_string(),
+            driver: DatabaseDriver::Sqlite,
+            database: path.to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        let mut service = SqliteService::new();
+        service.connect(&config).await.unwrap();
+        service
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_schema_and_keys() {
+        let dir = tempdir().unwrap();
+        let mut service = connected_service(&dir.path().join("app.db")).await;
+
+        assert_eq!(service.get_databases().await.unwrap(), vec!["main".to_string()]);
+        assert_eq!(service.get_tables(None).await.unwrap(), vec!["users".to_string()]);
+
+        let schema = service.get_schema(None).await.unwrap();
+        assert_eq!(
+            schema.get("users").unwrap(),
+            &vec!["id".to_string(), "name".to_string(), "active".to_string(), "score".to_string()]
+        );
+
+        let keys = service.get_primary_keys("users".to_string()).await.unwrap();
+        assert_eq!(keys, vec!["id".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_execute_and_update() {
+        let dir = tempdir().unwrap();
+        let service = connected_service(&dir.path().join("app.db")).await;
+
+        let mut changes = HashMap::new();
+        changes.insert("name".to_string(), json!("Bobby"));
+        let mut pks = HashMap::new();
+        pks.insert("id".to_string(), json!(2));
+        let update = RowUpdate {
+            table_name: "users".to_string(),
+            primary_keys: pks,
+            changes,
+        };
+        let res = service.update_rows(vec![update]).await.unwrap();
+        assert_eq!(res.affected_rows, 1);
+
+        let result = service
+            .execute("SELECT * FROM users ORDER BY id", None)
+            .await
+            .unwrap();
+        assert!(result.error.is_none());
+        let names: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "name", "active", "score"]);
+        assert_eq!(
+            result.rows[0],
+            vec![json!(1), json!("Alice"), json!(true), json!(1.5)]
+        );
+        assert_eq!(
+            result.rows[1],
+            vec![json!(2), json!("Bobby"), json!(false), json!(null)]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_repeated_column_names() {
+        let dir = tempdir().unwrap();
+        let service = connected_service(&dir.path().join("app.db")).await;
+
+        let result = service
+            .execute(
+                "SELECT a.id, b.id, a.name FROM users a JOIN users b ON b.id = a.id + 1",
+                None,
+            )
+            .await
+            .unwrap();
+        let names: Vec<_> = result.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["id", "id", "name"]);
+        assert_eq!(result.rows, vec![vec![json!(1), json!(2), json!("Alice")]]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_rows_affected() {
+        let dir = tempdir().unwrap();
+        let service = connected_service(&dir.path().join("app.db")).await;
+
+        let result = service
+            .execute("UPDATE users SET score = 2 WHERE score IS NOT NULL", None)
+            .await
+            .unwrap();
+        assert!(result.error.is_none());
+        assert!(result.columns.is_empty());
+        assert_eq!(result.rows_affected, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_execute_stream() {
+        let dir = tempdir().unwrap();
+        let service = connected_service(&dir.path().join("app.db")).await;
+
+        let batches = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let received = batches.clone();
+        let sink: BatchSink =
+            std::sync::Arc::new(move |batch| received.lock().unwrap().push(batch));
+        let sql = "WITH RECURSIVE n(x) AS (SELECT 1 UNION ALL SELECT x + 1 FROM n) \
+                   SELECT x FROM n";
+        let result = service.execute_stream(sql, None, 1200, sink).await.unwrap();
+        assert!(result.error.is_none());
+        assert!(result.truncated);
+        assert!(result.rows.is_empty());
+        assert_eq!(result.rows_affected, None);
+        assert_eq!(result.columns.len(), 1);
+
+        // The pool hands out a fresh connection after the cut off query
+        let result = service
+            .execute("SELECT COUNT(*) FROM users", None)
+            .await
+            .unwrap();
+        assert!(result.error.is_none());
+
+        let batches = batches.lock().unwrap();
+        let rows: Vec<_> = batches.iter().flat_map(|b| &b.rows).collect();
+        assert_eq!(rows.len(), 1200);
+        assert_eq!(rows[1199], &vec![json!(1200)]);
+        assert_eq!(
+            batches[0].columns.as_ref().map(|c| c[0].name.as_str()),
+            Some("x")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_column_metadata() {
+        let dir = tempdir().unwrap();
+        let service = connected_service(&dir.path().join("app.db")).await;
+
+        let result = service
+            .execute("SELECT id, name, score, COUNT(*) AS n FROM users WHERE id = 1", None)
+            .await
+            .unwrap();
+        let types: Vec<_> = result
+            .columns
+            .iter()
+            .map(|c| (c.name.as_str(), c.type_name.as_str(), c.logical_type, c.nullable))
+            .collect();
+        assert_eq!(
+            types,
+            vec![
+                ("id", "INTEGER", LogicalType::Integer, Some(true)),
+                ("name", "TEXT", LogicalType::Text, Some(true)),
+                ("score", "REAL", LogicalType::Float, Some(true)),
+                ("n", "INTEGER", LogicalType::Integer, Some(false)),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_init_script() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        connected_service(&path).await;
+
+        let config = ConnectionConfig {
+            driver: DatabaseDriver::Sqlite,
+            database: path.to_string_lossy().to_string(),
+            init_script: Some("PRAGMA foreign_keys = ON;\nPRAGMA cache_size = -4000;".to_string()),
+            ..Default::default()
+        };
+        let mut service = SqliteService::new();
+        service.connect(&config).await.unwrap();
+
+        let result = service.execute("PRAGMA foreign_keys", None).await.unwrap();
+        assert_eq!(result.rows[0], vec![json!(1)]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_session_variables() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        connected_service(&path).await;
+
+        let config = ConnectionConfig {
+            driver: DatabaseDriver::Sqlite,
+            database: path.to_string_lossy().to_string(),
+            session_variables: Some(vec![
+                SessionVariable {
+                    name: "cache_size".to_string(),
+                    value: "-4000".to_string(),
+                },
+                SessionVariable {
+                    name: "temp_store".to_string(),
+                    value: "MEMORY".to_string(),
+                },
+            ]),
+            ..Default::default()
+        };
+        let mut service = SqliteService::new();
+        service.connect(&config).await.unwrap();
+
+        let result = service.execute("PRAGMA cache_size", None).await.unwrap();
+        assert_eq!(result.rows[0], vec![json!(-4000)]);
+        let result = service.execute("PRAGMA temp_store", None).await.unwrap();
+        assert_eq!(result.rows[0], vec![json!(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_table_data_in_attached_schema() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("app.db");
+        connected_service(&path).await;
+
+        let other = dir.path().join("other.db");
+        let options = SqliteConnectOptions::new()
+            .filename(&other)
+            .create_if_missing(true);
+        let pool = SqlitePoolOptions::new()
+            .connect_with(options)
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE events (id INTEGER PRIMARY KEY, kind TEXT)")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO events (kind) VALUES ('login')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool.close().await;
+
+        let config = ConnectionConfig {
+            driver: DatabaseDriver::Sqlite,
+            database: path.to_string_lossy().to_string(),
+            init_script: Some(format!("ATTACH DATABASE '{}' AS other", other.display())),
+            ..Default::default()
+        };
+        let mut service = SqliteService::new();
+        service.connect(&config).await.unwrap();
+        service
+            .set_active_database("other".to_string())
+            .await
+            .unwrap();
+
+        let request = |table_name: &str| DataRequest {
+            table_name: table_name.to_string(),
+            offset: 0,
+            limit: 10,
+            sort: None,
+        };
+        let events = service.get_table_data(request("events")).await.unwrap();
+        assert_eq!(events.rows, vec![vec![json!(1), json!("login")]]);
+
+        // Edits go back to the attached database, not to main
+        let mut changes = HashMap::new();
+        changes.insert("kind".to_string(), json!("logout"));
+        let mut pks = HashMap::new();
+        pks.insert("id".to_string(), json!(1));
+        let update = RowUpdate {
+            table_name: "events".to_string(),
+            primary_keys: pks,
+            changes,
+        };
+        let res = service.update_rows(vec![update]).await.unwrap();
+        assert_eq!(res.affected_rows, 1);
+        let events = service.get_table_data(request("events")).await.unwrap();
+        assert_eq!(events.rows, vec![vec![json!(1), json!("logout")]]);
+
+        let users = service.get_table_data(request("main.users")).await.unwrap();
+        assert_eq!(users.rows.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_attach_from_query() {
+        let dir = tempdir().unwrap();
+        let mut service = connected_service(&dir.path().join("app.db")).await;
+        assert_eq!(service.pool().unwrap().options().get_max_connections(), 1);
+
+        // The connection doesn't create missing files, ATTACH included
+        let other = dir.path().join("other.db");
+        std::fs::File::create(&other).unwrap();
+        let attach = format!("ATTACH DATABASE '{}' AS other", other.display());
+        let result = service.execute(&attach, None).await.unwrap();
+        assert_eq!(result.error, None);
+        let result = service
+            .execute("CREATE TABLE other.events (id INTEGER PRIMARY KEY)", None)
+            .await
+            .unwrap();
+        assert_eq!(result.error, None);
+
+        let databases = service.get_databases().await.unwrap();
+        assert_eq!(databases, vec!["main".to_string(), "other".to_string()]);
+        let tables = service.get_tables(Some("other".to_string())).await.unwrap();
+        assert_eq!(tables, vec!["events".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_execute_script() {
+        let dir = tempdir().unwrap();
+        let service = connected_service(&dir.path().join("app.db")).await;
+
+        let statements = [
+            "CREATE TEMP TABLE seen (id INTEGER)",
+            "INSERT INTO seen SELECT id FROM users",
+            "SELECT COUNT(*) AS n FROM seen",
+            "SELECT * FROM missing",
+            "DELETE FROM seen",
+        ];
+        let results = service
+            .execute_script(statements.map(String::from).to_vec(), true)
+            .await
+            .unwrap();
+
+        // The temp table is only visible because every statement ran on one connection
+        let statuses: Vec<_> = results.iter().map(|r| r.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                StatementStatus::Success,
+                StatementStatus::Success,
+                StatementStatus::Success,
+                StatementStatus::Error,
+                StatementStatus::Skipped
+            ]
+        );
+        assert_eq!(results[1].result.rows_affected, Some(2));
+        assert_eq!(results[2].result.rows[0], vec![json!(2)]);
+
+        let results = service
+            .execute_script(
+                vec![statements[3].to_string(), "SELECT 1".to_string()],
+                false,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results[1].status, StatementStatus::Success);
+
+        // A transaction left open is rolled back
+        let results = service
+            .execute_script(
+                vec!["BEGIN".to_string(), "DELETE FROM users".to_string()],
+                true,
+            )
+            .await
+            .unwrap();
+        assert_eq!(results[1].result.rows_affected, Some(2));
+        let result = service
+            .execute("SELECT COUNT(*) FROM users", None)
+            .await
+            .unwrap();
+        assert_eq!(result.rows[0], vec![json!(2)]);
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_missing_file() {
+        let dir = tempdir().unwrap();
+        let mut service = SqliteService::new();
+        let config = ConnectionConfig {
+            id: "sqlite".to_string(),
+            driver: DatabaseDriver::Sqlite,
+            database: dir.path().join("missing.db").to_string_lossy().to_string(),
+            ..Default::default()
+        };
+
+        assert!(service.connect(&config).await.is_err());
+    }
+}
@@ -0,0 +1,339 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Values resolved for a host alias from an OpenSSH client config. Only the options
+// that matter for building a tunnel are kept.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SshHostConfig {
+    pub host_name: Option<String>,
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub identity_files: Vec<String>,
+    pub proxy_jump: Option<String>,
+}
+
+struct HostBlock {
+    patterns: Vec<String>,
+    options: Vec<(String, String)>,
+}
+
+pub struct SshConfig {
+    blocks: Vec<HostBlock>,
+    ssh_dir: PathBuf,
+}
+
+const MAX_INCLUDE_DEPTH: usize = 8;
+
+impl SshConfig {
+    // Reads ~/.ssh/config. A missing or unreadable file yields an empty config, so
+    // aliases simply resolve to themselves.
+    pub fn load() -> Self {
+        let ssh_dir = home_dir().join(".ssh");
+        let content = fs::read_to_string(ssh_dir.join("config")).unwrap_or_default();
+        Self::parse_with_dir(&content, ssh_dir)
+    }
+
+    pub fn parse(content: &str) -> Self {
+        Self::parse_with_dir(content, home_dir().join(".ssh"))
+    }
+
+    fn parse_with_dir(content: &str, ssh_dir: PathBuf) -> Self {
+        let mut config = SshConfig {
+            blocks: Vec::new(),
+            ssh_dir,
+        };
+        // Options before the first Host line apply to every host
+        config.parse_into(content, vec!["*".to_string()], 0);
+        config
+    }
+
+    fn parse_into(&mut self, content: &str, patterns: Vec<String>, depth: usize) {
+        self.blocks.push(HostBlock {
+            patterns,
+            options: Vec::new(),
+        });
+
+        for line in content.lines() {
+            let Some((key, value)) = split_option(line) else {
+                continue;
+            };
+
+            match key.as_str() {
+                "host" => self.blocks.push(HostBlock {
+                    patterns: value.split_whitespace().map(|p| p.to_string()).collect(),
+                    options: Vec::new(),
+                }),
+                // Match criteria are not evaluated, such blocks never apply
+                "match" => self.blocks.push(HostBlock {
+                    patterns: Vec::new(),
+                    options: Vec::new(),
+                }),
+                "include" => {
+                    if depth >= MAX_INCLUDE_DEPTH {
+                        log::warn!("ssh config: Include nested too deeply, ignoring");
+                        continue;
+                    }
+                    let current = self
+                        .blocks
+                        .last()
+                        .map(|b| b.patterns.clone())
+                        .unwrap_or_default();
+                    let paths: Vec<PathBuf> = value
+                        .split_whitespace()
+                        .flat_map(|p| self.expand_include(p))
+                        .collect();
+                    for path in paths {
+                        if let Ok(included) = fs::read_to_string(&path) {
+                            self.parse_into(&included, current.clone(), depth + 1);
+                        }
+                    }
+                    // Lines after the Include still belong to the enclosing block
+                    self.blocks.push(HostBlock {
+                        patterns: current,
+                        options: Vec::new(),
+                    });
+                }
+                _ => {
+                    if let Some(block) = self.blocks.last_mut() {
+                        block.options.push((key, value));
+                    }
+                }
+            }
+        }
+    }
+
+    fn expand_include(&self, raw: &str) -> Vec<PathBuf> {
+        let path = if let Some(rest) = raw.strip_prefix("~/") {
+            home_dir().join(rest)
+        } else if Path::new(raw).is_absolute() {
+            PathBuf::from(raw)
+        } else {
+            self.ssh_dir.join(raw)
+        };
+
+        let Some(file_name) = path.file_name().and_then(|f| f.to_str()) else {
+            return Vec::new();
+        };
+        if !file_name.contains('*') && !file_name.contains('?') {
+            return vec![path];
+        }
+
+        let Some(dir) = path.parent() else {
+            return Vec::new();
+        };
+        let mut matches: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| {
+                entries
+                    .filter_map(|e| e.ok())
+                    .filter(|e| {
+                        e.file_name()
+                            .to_str()
+                            .map(|name| wildcard_match(file_name, name))
+                            .unwrap_or(false)
+                    })
+                    .map(|e| e.path())
+                    .collect()
+            })
+            .unwrap_or_default();
+        matches.sort();
+        matches
+    }
+
+    // Same precedence as ssh(1): the first value found for an option wins, except
+    // IdentityFile which accumulates.
+    pub fn resolve(&self, alias: &str) -> SshHostConfig {
+        let mut result = SshHostConfig::default();
+
+        for block in self
+            .blocks
+            .iter()
+            .filter(|b| host_matches(alias, &b.patterns))
+        {
+            for (key, value) in &block.options {
+                match key.as_str() {
+                    "hostname" if result.host_name.is_none() => {
+                        result.host_name = Some(value.replace("%h", alias))
+                    }
+                    "port" if result.port.is_none() => result.port = value.parse().ok(),
+                    "user" if result.user.is_none() => result.user = Some(value.clone()),
+                    "identityfile" => result.identity_files.push(value.clone()),
+                    "proxyjump" if result.proxy_jump.is_none() => {
+                        result.proxy_jump = Some(value.clone())
+                    }
+                    _ => {}
+                }
+            }
+        }
+
+        if result.proxy_jump.as_deref() == Some("none") {
+            result.proxy_jump = None;
+        }
+        result
+    }
+}
+
+pub(crate) fn home_dir() -> PathBuf {
+    dirs::home_dir().unwrap_or_else(|| PathBuf::from("."))
+}
+
+fn split_option(line: &str) -> Option<(String, String)> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return None;
+    }
+
+    let split_at = line.find(|c: char| c.is_whitespace() || c == '=')?;
+    let key = line[..split_at].to_lowercase();
+    let value = line[split_at..]
+        .trim_start_matches(|c: char| c.is_whitespace() || c == '=')
+        .trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|v| v.strip_suffix('"'))
+        .unwrap_or(value);
+
+    Some((key, value.to_string()))
+}
+
+fn host_matches(host: &str, patterns: &[String]) -> bool {
+    let mut matched = false;
+    for pattern in patterns {
+        if let Some(negated) = pattern.strip_prefix('!') {
+            if wildcard_match(negated, host) {
+                return false;
+            }
+        } else if wildcard_match(pattern, host) {
+            matched = true;
+        }
+    }
+    matched
+}
+
+fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    fn matches(p: &[char], t: &[char]) -> bool {
+        match p.first() {
+            None => t.is_empty(),
+            Some('*') => matches(&p[1..], t) || (!t.is_empty() && matches(p, &t[1..])),
+            Some('?') => !t.is_empty() && matches(&p[1..], &t[1..]),
+            Some(c) => {
+                t.first().is_some_and(|tc| tc.eq_ignore_ascii_case(c)) && matches(&p[1..], &t[1..])
+            }
+        }
+    }
+
+    matches(&pattern, &text)
+}
+
+// Expands `~` and the %-tokens ssh(1) supports in IdentityFile.
+pub fn expand_path(raw: &str, host: &str, user: &str) -> PathBuf {
+    let home = home_dir();
+    let local_user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .unwrap_or_default();
+
+    let mut expanded = String::new();
+    let mut chars = raw.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            expanded.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('d') => expanded.push_str(&home.to_string_lossy()),
+            Some('h') => expanded.push_str(host),
+            Some('r') => expanded.push_str(user),
+            Some('u') => expanded.push_str(&local_user),
+            Some('%') => expanded.push('%'),
+            Some(other) => {
+                expanded.push('%');
+                expanded.push(other);
+            }
+            None => expanded.push('%'),
+        }
+    }
+
+    match expanded.strip_prefix("~/") {
+        Some(rest) => home.join(rest),
+        None => PathBuf::from(expanded),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = "
+# global defaults
+ServerAliveInterval 30
+
+Host bastion-prod
+    HostName 203.0.113.10
+    User deploy
+    Port 2222
+    IdentityFile ~/.ssh/prod_ed25519
+
+Host db-* !db-legacy
+    ProxyJump bastion-prod
+    User dba
+
+Host *.internal
+    HostName %h
+    ProxyJump=jump.example.com:2200
+
+Host *
+    User fallback
+    IdentityFile ~/.ssh/id_ed25519
+";
+
+    #[test]
+    fn test_resolve_alias() {
+        let config = SshConfig::parse(SAMPLE);
+        let resolved = config.resolve("bastion-prod");
+
+        assert_eq!(resolved.host_name.as_deref(), Some("203.0.113.10"));
+        assert_eq!(resolved.port, Some(2222));
+        assert_eq!(resolved.user.as_deref(), Some("deploy"));
+        assert_eq!(
+            resolved.identity_files,
+            vec![
+                "~/.ssh/prod_ed25519".to_string(),
+                "~/.ssh/id_ed25519".to_string()
+            ]
+        );
+        assert_eq!(resolved.proxy_jump, None);
+    }
+
+    #[test]
+    fn test_resolve_patterns_and_negation() {
+        let config = SshConfig::parse(SAMPLE);
+
+        let db = config.resolve("db-orders");
+        assert_eq!(db.proxy_jump.as_deref(), Some("bastion-prod"));
+        assert_eq!(db.user.as_deref(), Some("dba"));
+        assert_eq!(db.host_name, None);
+
+        let legacy = config.resolve("db-legacy");
+        assert_eq!(legacy.proxy_jump, None);
+        assert_eq!(legacy.user.as_deref(), Some("fallback"));
+
+        let internal = config.resolve("pg.internal");
+        assert_eq!(internal.host_name.as_deref(), Some("pg.internal"));
+        assert_eq!(
+            internal.proxy_jump.as_deref(),
+            Some("jump.example.com:2200")
+        );
+    }
+
+    #[test]
+    fn test_expand_path_tokens() {
+        let path = expand_path("/keys/%r@%h%%", "db1", "admin");
+        assert_eq!(path, PathBuf::from("/keys/admin@db1%"));
+        assert_eq!(
+            expand_path("~/.ssh/id_rsa", "h", "u"),
+            home_dir().join(".ssh/id_rsa")
+        );
+    }
+}
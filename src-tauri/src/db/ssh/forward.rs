@@ -0,0 +1,298 @@
+use polling::{Event, Events, Poller};
+use ssh2::{BlockDirections, Channel, Session};
+use std::collections::HashMap;
+use std::io::{self, ErrorKind, Read, Write};
+use std::net::TcpStream;
+use std::sync::Arc;
+use std::time::Duration;
+
+const BUFFER_SIZE: usize = 32 * 1024;
+const SESSION_KEY: usize = 0;
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Pumped {
+    pub sent: usize,
+    pub received: usize,
+    pub done: bool,
+}
+
+impl Pumped {
+    pub fn is_idle(&self) -> bool {
+        self.sent == 0 && self.received == 0 && !self.done
+    }
+}
+
+// One forwarded TCP connection: a local socket paired with an SSH channel. Both sides
+// must be non-blocking, `pump` moves whatever is ready and never waits. Data that could
+// not be written yet stays buffered so nothing is lost on a partial write.
+pub struct Forward {
+    socket: TcpStream,
+    channel: Channel,
+    to_channel: Vec<u8>,
+    to_socket: Vec<u8>,
+    socket_eof: bool,
+    channel_eof: bool,
+}
+
+impl Forward {
+    pub fn new(socket: TcpStream, channel: Channel) -> Self {
+        Forward {
+            socket,
+            channel,
+            to_channel: Vec::new(),
+            to_socket: Vec::new(),
+            socket_eof: false,
+            channel_eof: false,
+        }
+    }
+
+    // Socket errors only end this forward. Channel errors are returned, since they may
+    // mean the whole session is gone.
+    pub fn pump(&mut self) -> io::Result<Pumped> {
+        let mut pumped = Pumped::default();
+        let mut buf = [0u8; BUFFER_SIZE];
+
+        if self.to_channel.is_empty() && !self.socket_eof {
+            match self.socket.read(&mut buf) {
+                Ok(0) => {
+                    self.socket_eof = true;
+                    let _ = self.channel.send_eof();
+                }
+                Ok(n) => self.to_channel.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => return Ok(self.finish(pumped)),
+            }
+        }
+        if !self.to_channel.is_empty() {
+            match self.channel.write(&self.to_channel) {
+                Ok(n) => {
+                    self.to_channel.drain(..n);
+                    pumped.sent = n;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        if self.to_socket.is_empty() && !self.channel_eof {
+            match self.channel.read(&mut buf) {
+                Ok(0) if self.channel.eof() => self.channel_eof = true,
+                Ok(0) => {}
+                Ok(n) => self.to_socket.extend_from_slice(&buf[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(e) => return Err(e),
+            }
+        }
+        if !self.to_socket.is_empty() {
+            match self.socket.write(&self.to_socket) {
+                Ok(n) => {
+                    self.to_socket.drain(..n);
+                    pumped.received = n;
+                }
+                Err(e) if e.kind() == ErrorKind::WouldBlock => {}
+                Err(_) => return Ok(self.finish(pumped)),
+            }
+        }
+
+        pumped.done = (self.channel_eof && self.to_socket.is_empty())
+            || (self.socket_eof && self.to_channel.is_empty());
+        Ok(pumped)
+    }
+
+    fn finish(&mut self, mut pumped: Pumped) -> Pumped {
+        let _ = self.channel.close();
+        pumped.done = true;
+        pumped
+    }
+
+    // libssh2 reads packets for every channel while serving one of them, so input can
+    // be waiting in its buffers with nothing left to read on the socket.
+    fn has_buffered_input(&self) -> bool {
+        self.to_socket.is_empty()
+            && !self.channel_eof
+            && (self.channel.read_window().available > 0 || self.channel.eof())
+    }
+
+    // The local socket only needs watching while there is room to read into or
+    // buffered data to write out. The channel side is covered by the session socket.
+    fn interest(&self, key: usize) -> Event {
+        Event::new(
+            key,
+            self.to_channel.is_empty() && !self.socket_eof,
+            !self.to_socket.is_empty(),
+        )
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Turn {
+    pub sent: usize,
+    pub received: usize,
+    pub channel_failed: bool,
+}
+
+struct Entry {
+    forward: Forward,
+    armed: Option<Event>,
+}
+
+// Drives every forward of one session. Work is done until all sides would block, then
+// the thread sleeps in the poller until the session socket or a local socket becomes
+// ready. libssh2 may read data for several channels at once, so every forward is
+// pumped after each wakeup rather than only the one whose socket fired.
+pub struct Reactor {
+    poller: Arc<Poller>,
+    events: Events,
+    session: Session,
+    socket: TcpStream,
+    session_armed: Option<Event>,
+    forwards: HashMap<usize, Entry>,
+    next_key: usize,
+}
+
+impl Reactor {
+    // `socket` is a second handle to the socket the session runs over
+    pub fn new(poller: Arc<Poller>, session: Session, socket: TcpStream) -> io::Result<Self> {
+        session.set_blocking(false);
+        // SAFETY: the socket is owned by the reactor and removed from the poller in Drop
+        unsafe { poller.add(&socket, Event::none(SESSION_KEY))? };
+        Ok(Reactor {
+            poller,
+            events: Events::new(),
+            session,
+            socket,
+            session_armed: Some(Event::none(SESSION_KEY)),
+            forwards: HashMap::new(),
+            next_key: SESSION_KEY + 1,
+        })
+    }
+
+    pub fn add(&mut self, forward: Forward) -> io::Result<()> {
+        let key = self.next_key;
+        self.next_key += 1;
+        // SAFETY: the socket is owned by the entry and removed from the poller before
+        // the entry is dropped
+        unsafe { self.poller.add(&forward.socket, Event::none(key))? };
+        self.forwards.insert(
+            key,
+            Entry {
+                forward,
+                armed: Some(Event::none(key)),
+            },
+        );
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.forwards.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.forwards.is_empty()
+    }
+
+    // Moves all data that is ready, then waits until something becomes ready again,
+    // the poller is notified or `timeout` passes.
+    pub fn turn(&mut self, timeout: Duration) -> io::Result<Turn> {
+        let mut turn = Turn::default();
+
+        let mut rechecked = false;
+        loop {
+            let mut progress = false;
+            let mut finished = Vec::new();
+            for (key, entry) in self.forwards.iter_mut() {
+                match entry.forward.pump() {
+                    Ok(pumped) => {
+                        progress |= !pumped.is_idle();
+                        turn.sent += pumped.sent;
+                        turn.received += pumped.received;
+                        if pumped.done {
+                            finished.push(*key);
+                        }
+                    }
+                    Err(e) => {
+                        log::debug!("SSH channel error: {}", e);
+                        turn.channel_failed = true;
+                        finished.push(*key);
+                    }
+                }
+            }
+            for key in finished {
+                self.remove(key);
+            }
+            if progress {
+                rechecked = false;
+                continue;
+            }
+            if rechecked
+                || !self
+                    .forwards
+                    .values()
+                    .any(|e| e.forward.has_buffered_input())
+            {
+                break;
+            }
+            rechecked = true;
+        }
+
+        self.arm()?;
+        self.events.clear();
+        self.poller.wait(&mut self.events, Some(timeout))?;
+        // Sources are registered in oneshot mode and have to be re-armed after firing
+        for event in self.events.iter() {
+            if event.key == SESSION_KEY {
+                self.session_armed = None;
+            } else if let Some(entry) = self.forwards.get_mut(&event.key) {
+                entry.armed = None;
+            }
+        }
+
+        Ok(turn)
+    }
+
+    fn arm(&mut self) -> io::Result<()> {
+        // Incoming data may arrive for any channel at any time, so the session socket is
+        // always watched for reads; writes only matter when libssh2 has output queued.
+        let session_wants = Event::new(
+            SESSION_KEY,
+            true,
+            matches!(
+                self.session.block_directions(),
+                BlockDirections::Outbound | BlockDirections::Both
+            ),
+        );
+        if !same_interest(self.session_armed, session_wants) {
+            self.poller.modify(&self.socket, session_wants)?;
+            self.session_armed = Some(session_wants);
+        }
+
+        for (key, entry) in self.forwards.iter_mut() {
+            let wants = entry.forward.interest(*key);
+            if !same_interest(entry.armed, wants) {
+                self.poller.modify(&entry.forward.socket, wants)?;
+                entry.armed = Some(wants);
+            }
+        }
+        Ok(())
+    }
+
+    fn remove(&mut self, key: usize) {
+        if let Some(entry) = self.forwards.remove(&key) {
+            let _ = self.poller.delete(&entry.forward.socket);
+        }
+    }
+}
+
+impl Drop for Reactor {
+    fn drop(&mut self) {
+        let keys: Vec<usize> = self.forwards.keys().copied().collect();
+        for key in keys {
+            self.remove(key);
+        }
+        let _ = self.poller.delete(&self.socket);
+    }
+}
+
+fn same_interest(armed: Option<Event>, wants: Event) -> bool {
+    armed.is_some_and(|a| a.readable == wants.readable && a.writable == wants.writable)
+}
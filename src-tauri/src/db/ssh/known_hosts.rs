@@ -0,0 +1,200 @@
+use base64::Engine;
+use ssh2::{CheckResult, HashType, HostKeyType, KnownHostFileKind, KnownHosts, Session};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::error::{DbError, Result};
+use crate::models::{HostKeyStatus, SshHostKey};
+
+// Host keys are checked against the user's ~/.ssh/known_hosts (never modified) and an
+// app-managed file that receives the keys confirmed in the UI. The app file is checked
+// first so a key the user explicitly re-confirmed wins over a stale OpenSSH entry.
+#[derive(Debug, Clone)]
+pub struct KnownHostsStore {
+    app_file: PathBuf,
+    user_file: PathBuf,
+}
+
+impl KnownHostsStore {
+    pub fn new() -> Self {
+        let home = super::config::home_dir();
+        Self::with_paths(
+            home.join(".gridly").join("known_hosts"),
+            home.join(".ssh").join("known_hosts"),
+        )
+    }
+
+    pub fn with_paths(app_file: PathBuf, user_file: PathBuf) -> Self {
+        KnownHostsStore {
+            app_file,
+            user_file,
+        }
+    }
+
+    pub fn check(&self, sess: &Session, host: &str, port: u16) -> Result<SshHostKey> {
+        let (key, key_type) = sess
+            .host_key()
+            .ok_or_else(|| DbError::Ssh(format!("{} did not send a host key", host)))?;
+
+        let app = self.load(sess, &self.app_file)?.check_port(host, port, key);
+        let status = match app {
+            CheckResult::Match => HostKeyStatus::Trusted,
+            _ => match self
+                .load(sess, &self.user_file)?
+                .check_port(host, port, key)
+            {
+                CheckResult::Match => HostKeyStatus::Trusted,
+                CheckResult::Mismatch => HostKeyStatus::Changed,
+                _ if matches!(app, CheckResult::Mismatch) => HostKeyStatus::Changed,
+                _ => HostKeyStatus::Unknown,
+            },
+        };
+
+        Ok(SshHostKey {
+            host: host.to_string(),
+            port,
+            key_type: key_type_name(key_type).to_string(),
+            fingerprint: fingerprint(sess).unwrap_or_default(),
+            status,
+        })
+    }
+
+    // Stores the session's current host key in the app file, replacing older entries
+    // for the same host and port.
+    pub fn trust(&self, sess: &Session, host: &str, port: u16) -> Result<()> {
+        let (key, key_type) = sess
+            .host_key()
+            .ok_or_else(|| DbError::Ssh(format!("{} did not send a host key", host)))?;
+
+        let name = entry_name(host, port);
+        let mut known_hosts = self.load(sess, &self.app_file)?;
+        for entry in known_hosts.iter().map_err(ssh_error)? {
+            if entry.name() == Some(name.as_str()) {
+                known_hosts.remove(&entry).map_err(ssh_error)?;
+            }
+        }
+        known_hosts
+            .add(&name, key, "added by gridly", key_type.into())
+            .map_err(ssh_error)?;
+
+        if let Some(dir) = self.app_file.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        known_hosts
+            .write_file(&self.app_file, KnownHostFileKind::OpenSSH)
+            .map_err(ssh_error)
+    }
+
+    // Lines are fed one by one so a single entry libssh2 cannot parse (e.g. a
+    // security key type) doesn't hide the rest of the file.
+    fn load(&self, sess: &Session, path: &Path) -> Result<KnownHosts> {
+        let mut known_hosts = sess.known_hosts().map_err(ssh_error)?;
+        if let Ok(content) = fs::read_to_string(path) {
+            for line in content.lines() {
+                let line = line.trim();
+                if line.is_empty() || line.starts_with('#') {
+                    continue;
+                }
+                if known_hosts
+                    .read_str(line, KnownHostFileKind::OpenSSH)
+                    .is_err()
+                {
+                    log::debug!(
+                        "Skipping unsupported known_hosts entry in {}",
+                        path.display()
+                    );
+                }
+            }
+        }
+        Ok(known_hosts)
+    }
+}
+
+impl Default for KnownHostsStore {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn ensure_trusted(key: &SshHostKey) -> Result<()> {
+    let host = format!("{}:{}", key.host, key.port);
+    match key.status {
+        HostKeyStatus::Trusted => Ok(()),
+        HostKeyStatus::Unknown => Err(DbError::SshHostKeyUnknown {
+            host,
+            fingerprint: key.fingerprint.clone(),
+        }),
+        HostKeyStatus::Changed => Err(DbError::SshHostKeyChanged {
+            host,
+            fingerprint: key.fingerprint.clone(),
+        }),
+    }
+}
+
+// Same format as `ssh-keygen -lf`: SHA256 digest, unpadded base64.
+fn fingerprint(sess: &Session) -> Option<String> {
+    sess.host_key_hash(HashType::Sha256).map(|hash| {
+        format!(
+            "SHA256:{}",
+            base64::engine::general_purpose::STANDARD_NO_PAD.encode(hash)
+        )
+    })
+}
+
+fn entry_name(host: &str, port: u16) -> String {
+    if port == 22 {
+        host.to_string()
+    } else {
+        format!("[{}]:{}", host, port)
+    }
+}
+
+fn key_type_name(key_type: HostKeyType) -> &'static str {
+    match key_type {
+        HostKeyType::Rsa => "ssh-rsa",
+        HostKeyType::Dss => "ssh-dss",
+        HostKeyType::Ecdsa256 => "ecdsa-sha2-nistp256",
+        HostKeyType::Ecdsa384 => "ecdsa-sha2-nistp384",
+        HostKeyType::Ecdsa521 => "ecdsa-sha2-nistp521",
+        HostKeyType::Ed25519 => "ssh-ed25519",
+        HostKeyType::Unknown => "unknown",
+    }
+}
+
+fn ssh_error(e: ssh2::Error) -> DbError {
+    DbError::Ssh(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_entry_name() {
+        assert_eq!(entry_name("bastion", 22), "bastion");
+        assert_eq!(entry_name("10.0.0.5", 2222), "[10.0.0.5]:2222");
+    }
+
+    #[test]
+    fn test_ensure_trusted_maps_status_to_error() {
+        let mut key = SshHostKey {
+            host: "bastion".to_string(),
+            port: 22,
+            key_type: "ssh-ed25519".to_string(),
+            fingerprint: "SHA256:abc".to_string(),
+            status: HostKeyStatus::Trusted,
+        };
+        assert!(ensure_trusted(&key).is_ok());
+
+        key.status = HostKeyStatus::Unknown;
+        assert!(matches!(
+            ensure_trusted(&key),
+            Err(DbError::SshHostKeyUnknown { .. })
+        ));
+
+        key.status = HostKeyStatus::Changed;
+        let err = ensure_trusted(&key).unwrap_err();
+        assert!(matches!(err, DbError::SshHostKeyChanged { .. }));
+        assert!(err.to_string().contains("bastion:22"));
+    }
+}
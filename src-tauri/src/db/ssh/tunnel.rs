@@ -0,0 +1,405 @@
+use polling::Poller;
+use ssh2::Session;
+use std::io;
+use std::net::TcpStream;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::net::TcpListener;
+
+use super::forward::{Forward, Reactor};
+use super::{open_session, resolve_hops, KnownHostsStore, SshConfig, SshHop, SshSession};
+use crate::error::{DbError, Result};
+use crate::models::{ConnectionConfig, TunnelState, TunnelStatus};
+
+const CHANNEL_OPEN_TIMEOUT: Duration = Duration::from_secs(10);
+const CHANNEL_OPEN_POLL: Duration = Duration::from_millis(10);
+const KEEPALIVE_INTERVAL_SECS: u32 = 30;
+const MAX_RECONNECT_ATTEMPTS: u32 = 5;
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+// All TCP connections of a tunnel are multiplexed as channels over one authenticated
+// session. The session lives on a dedicated worker thread; the async accept loop only
+// hands accepted sockets over to it.
+pub struct SshTunnelService {
+    stop_signal: Option<tokio::sync::oneshot::Sender<()>>,
+    status: Arc<Mutex<TunnelStatus>>,
+    known_hosts: KnownHostsStore,
+}
+
+impl SshTunnelService {
+    pub fn new() -> Self {
+        Self::with_known_hosts(KnownHostsStore::new())
+    }
+
+    pub fn with_known_hosts(known_hosts: KnownHostsStore) -> Self {
+        SshTunnelService {
+            stop_signal: None,
+            status: Arc::new(Mutex::new(TunnelStatus::default())),
+            known_hosts,
+        }
+    }
+
+    pub async fn create_tunnel(
+        &mut self,
+        config: &ConnectionConfig,
+        remote_host: &str,
+        remote_port: u16,
+    ) -> Result<u16> {
+        let hops = resolve_hops(config, &SshConfig::load())?;
+        let known_hosts = self.known_hosts.clone();
+
+        // Connect and authenticate once up front, so an unreachable bastion or bad
+        // credentials fail the connect instead of showing up as a reset in the driver
+        self.status.lock().unwrap().state = TunnelState::Connecting;
+        let session = {
+            let hops = hops.clone();
+            let known_hosts = known_hosts.clone();
+            tokio::task::spawn_blocking(move || open_session(&hops, &known_hosts))
+                .await
+                .map_err(|e| DbError::Ssh(e.to_string()))?
+        };
+        let session = match session {
+            Ok(session) => session,
+            Err(e) => {
+                let mut status = self.status.lock().unwrap();
+                status.state = TunnelState::Failed;
+                status.last_error = Some(e.to_string());
+                return Err(e);
+            }
+        };
+
+        let listener = TcpListener::bind("127.0.0.1:0").await?;
+        let local_port = listener.local_addr()?.port();
+
+        *self.status.lock().unwrap() = TunnelStatus {
+            state: TunnelState::Connected,
+            local_port,
+            ..Default::default()
+        };
+
+        // New sockets are queued for the worker and the poller is woken up to pick them
+        // up, since the worker sleeps in it while the tunnel is idle
+        let poller = Arc::new(Poller::new()?);
+        let (socket_tx, socket_rx) = mpsc::channel::<TcpStream>();
+        let worker = TunnelWorker {
+            hops,
+            known_hosts,
+            remote_host: remote_host.to_string(),
+            remote_port,
+            incoming: socket_rx,
+            poller: poller.clone(),
+            status: self.status.clone(),
+        };
+        std::thread::Builder::new()
+            .name(format!("ssh-tunnel-{}", local_port))
+            .spawn(move || worker.run(session))?;
+
+        let (tx, mut rx) = tokio::sync::oneshot::channel::<()>();
+        self.stop_signal = Some(tx);
+
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = &mut rx => {
+                        log::info!("Stopping SSH tunnel");
+                        break;
+                    }
+                    Ok((socket, _)) = listener.accept() => {
+                        let Ok(socket) = socket.into_std() else {
+                            continue;
+                        };
+                        if socket.set_nonblocking(true).is_err() {
+                            continue;
+                        }
+                        if socket_tx.send(socket).is_err() {
+                            break;
+                        }
+                        let _ = poller.notify();
+                    }
+                }
+            }
+            // Dropping the sender tells the worker to shut down
+            drop(socket_tx);
+            let _ = poller.notify();
+        });
+
+        Ok(local_port)
+    }
+
+    pub fn status(&self) -> TunnelStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn close(&mut self) {
+        if let Some(tx) = self.stop_signal.take() {
+            let _ = tx.send(());
+        }
+        self.status.lock().unwrap().state = TunnelState::Closed;
+    }
+}
+
+impl Default for SshTunnelService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Served {
+    Stopped,
+    Dropped(String),
+}
+
+struct TunnelWorker {
+    hops: Vec<SshHop>,
+    known_hosts: KnownHostsStore,
+    remote_host: String,
+    remote_port: u16,
+    incoming: Receiver<TcpStream>,
+    poller: Arc<Poller>,
+    status: Arc<Mutex<TunnelStatus>>,
+}
+
+impl TunnelWorker {
+    // Starts with the session opened by `create_tunnel`. After a session drops, it is
+    // re-established in the background with backoff, but only a few times so a server
+    // rejecting us isn't hammered with logins. Past that, every new connection
+    // triggers one more attempt.
+    fn run(self, session: SshSession) {
+        let mut pending: Vec<TcpStream> = Vec::new();
+        let mut failed_attempts = 0u32;
+        let mut session = Some(session);
+
+        loop {
+            let ssh = match session.take() {
+                Some(ssh) => ssh,
+                None => {
+                    if pending.is_empty() && failed_attempts >= MAX_RECONNECT_ATTEMPTS {
+                        match self.incoming.recv() {
+                            Ok(socket) => pending.push(socket),
+                            Err(_) => break,
+                        }
+                    }
+
+                    self.update(|s| s.state = TunnelState::Reconnecting);
+                    match open_session(&self.hops, &self.known_hosts) {
+                        Ok(ssh) => {
+                            failed_attempts = 0;
+                            self.update(|s| {
+                                s.reconnects += 1;
+                                s.state = TunnelState::Connected;
+                                s.last_error = None;
+                            });
+                            ssh
+                        }
+                        Err(e) => {
+                            log::error!("SSH tunnel: {}", e);
+                            failed_attempts += 1;
+                            self.update(|s| {
+                                s.state = TunnelState::Failed;
+                                s.last_error = Some(e.to_string());
+                            });
+                            // Close waiting connections so drivers fail fast instead of hanging
+                            pending.clear();
+
+                            if failed_attempts < MAX_RECONNECT_ATTEMPTS {
+                                match self.incoming.recv_timeout(backoff(failed_attempts)) {
+                                    Ok(socket) => pending.push(socket),
+                                    Err(RecvTimeoutError::Timeout) => {}
+                                    Err(RecvTimeoutError::Disconnected) => break,
+                                }
+                            }
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            match self.serve(ssh, &mut pending) {
+                Served::Stopped => break,
+                Served::Dropped(error) => {
+                    log::warn!("SSH tunnel session dropped: {}", error);
+                    self.update(|s| {
+                        s.state = TunnelState::Reconnecting;
+                        s.active_channels = 0;
+                        s.last_error = Some(error);
+                    });
+                }
+            }
+        }
+
+        self.update(|s| {
+            s.state = TunnelState::Closed;
+            s.active_channels = 0;
+        });
+    }
+
+    fn serve(&self, ssh: SshSession, pending: &mut Vec<TcpStream>) -> Served {
+        let sess = ssh.session.clone();
+        sess.set_keepalive(true, KEEPALIVE_INTERVAL_SECS);
+
+        let mut reactor = match Reactor::new(self.poller.clone(), ssh.session, ssh.socket) {
+            Ok(reactor) => reactor,
+            Err(e) => return Served::Dropped(e.to_string()),
+        };
+        let mut next_keepalive = Instant::now();
+        let mut opening_since: Option<Instant> = None;
+
+        loop {
+            loop {
+                match self.incoming.try_recv() {
+                    Ok(socket) => pending.push(socket),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => return Served::Stopped,
+                }
+            }
+
+            // Channels open one at a time: libssh2 keeps the progress of an open in the
+            // session and resumes it on the next call. The session stays non-blocking,
+            // so the other channels keep moving while the server answers.
+            while let Some(socket) = pending.pop() {
+                let started = *opening_since.get_or_insert_with(Instant::now);
+                match sess.channel_direct_tcpip(&self.remote_host, self.remote_port, None) {
+                    Ok(channel) => {
+                        opening_since = None;
+                        if let Err(e) = reactor.add(Forward::new(socket, channel)) {
+                            log::error!("Failed to watch forwarded connection: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        let e = io::Error::from(e);
+                        if e.kind() == io::ErrorKind::WouldBlock
+                            && started.elapsed() < CHANNEL_OPEN_TIMEOUT
+                        {
+                            pending.push(socket);
+                            break;
+                        }
+                        opening_since = None;
+                        if let Err(dropped) = check_alive(&sess) {
+                            pending.push(socket);
+                            return Served::Dropped(dropped);
+                        }
+                        let error = format!(
+                            "Failed to open SSH channel to {}:{}: {}",
+                            self.remote_host, self.remote_port, e
+                        );
+                        log::error!("{}", error);
+                        // Recorded before the socket is dropped, so whoever sees the
+                        // connection reset can find out why
+                        self.update(|s| s.last_error = Some(error));
+                    }
+                }
+            }
+
+            let mut wait = next_keepalive.saturating_duration_since(Instant::now());
+            if opening_since.is_some() {
+                // The reply may already have been read along with another channel's data,
+                // leaving nothing on the socket to wake up for
+                wait = wait.min(CHANNEL_OPEN_POLL);
+            }
+            let turn = match reactor.turn(wait) {
+                Ok(turn) => turn,
+                Err(e) => return Served::Dropped(e.to_string()),
+            };
+            let active = reactor.len();
+            self.update(|s| {
+                s.active_channels = active;
+                s.bytes_sent += turn.sent as u64;
+                s.bytes_received += turn.received as u64;
+            });
+
+            if turn.channel_failed || Instant::now() >= next_keepalive {
+                match check_alive(&sess) {
+                    Ok(next) => next_keepalive = Instant::now() + next,
+                    Err(dropped) => return Served::Dropped(dropped),
+                }
+            }
+        }
+    }
+
+    fn update(&self, apply: impl FnOnce(&mut TunnelStatus)) {
+        apply(&mut self.status.lock().unwrap());
+    }
+}
+
+// Sends a keepalive to find out whether the session still works. Returns how long to
+// wait until the next one.
+fn check_alive(sess: &Session) -> std::result::Result<Duration, String> {
+    match sess.keepalive_send() {
+        Ok(seconds) => Ok(Duration::from_secs(seconds.max(1) as u64)),
+        Err(e) => {
+            let error = io::Error::from(e);
+            // Could not be sent right now, but the session itself is fine
+            if error.kind() == io::ErrorKind::WouldBlock {
+                Ok(Duration::from_secs(1))
+            } else {
+                Err(error.to_string())
+            }
+        }
+    }
+}
+
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs(1u64 << attempt.saturating_sub(1).min(5)).min(MAX_RECONNECT_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_backoff_is_capped() {
+        assert_eq!(backoff(1), Duration::from_secs(1));
+        assert_eq!(backoff(3), Duration::from_secs(4));
+        assert_eq!(backoff(10), MAX_RECONNECT_DELAY);
+    }
+
+    fn tunnel_config(ssh_port: u16) -> ConnectionConfig {
+        ConnectionConfig {
+            use_ssh: Some(true),
+            ssh_host: Some("127.0.0.1".to_string()),
+            ssh_port: Some(ssh_port),
+            ssh_user: Some("nobody".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_server_fails_create_tunnel() {
+        let closed_port = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        let mut tunnel = SshTunnelService::new();
+        let err = tunnel
+            .create_tunnel(&tunnel_config(closed_port), "db", 5432)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, DbError::Ssh(msg) if msg.contains("Failed to connect")));
+
+        let status = tunnel.status();
+        assert_eq!(status.state, TunnelState::Failed);
+        assert_eq!(status.last_error, Some(err.to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_non_ssh_server_fails_handshake() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            if let Ok((mut socket, _)) = listener.accept() {
+                let _ = socket.write_all(b"HTTP/1.1 400 Bad Request\r\n\r\n");
+            }
+        });
+
+        let mut tunnel = SshTunnelService::new();
+        let err = tunnel
+            .create_tunnel(&tunnel_config(port), "db", 5432)
+            .await
+            .unwrap_err();
+        assert!(matches!(&err, DbError::Ssh(msg) if msg.contains("Handshake")));
+    }
+}
@@ -1,7 +1,8 @@
+use crate::db::common::BatchSink;
 use crate::error::Result;
 use crate::models::{
     ConnectionConfig, DashboardMetrics, DataRequest, DbSchema, QueryResult, RowUpdate,
-    UpdateResult,
+    StatementResult, UpdateResult,
 };
 use async_trait::async_trait;
 
@@ -11,6 +12,16 @@ pub trait DatabaseService: Send + Sync {
     async fn connect(&mut self, config: &ConnectionConfig) -> Result<String>;
     async fn disconnect(&mut self) -> Result<()>;
     async fn execute(&self, sql: &str, query_id: Option<String>) -> Result<QueryResult>;
+    // Like `execute`, but the rows go to `sink` in batches as they are read instead of
+    // into the result, and reading stops after `max_rows`. Errors are for the connection
+    // itself and only happen before any rows were sent.
+    async fn execute_stream(
+        &self,
+        sql: &str,
+        query_id: Option<String>,
+        max_rows: usize,
+        sink: BatchSink,
+    ) -> Result<QueryResult>;
     async fn cancel_query(&self, query_id: String) -> Result<()>;
     async fn get_tables(&mut self, db_name: Option<String>) -> Result<Vec<String>>;
     async fn get_databases(&self) -> Result<Vec<String>>;
@@ -20,4 +31,31 @@ pub trait DatabaseService: Send + Sync {
     async fn get_primary_keys(&self, table_name: String) -> Result<Vec<String>>;
     async fn update_rows(&self, updates: Vec<RowUpdate>) -> Result<UpdateResult>;
     async fn get_dashboard_metrics(&self) -> Result<DashboardMetrics>;
+
+    // Runs statements one request at a time. Drivers with pooled sessions override this
+    // to keep the whole script on one connection. Errors are for the connection itself;
+    // failing statements are reported in their results.
+    async fn execute_script(
+        &self,
+        statements: Vec<String>,
+        stop_on_error: bool,
+    ) -> Result<Vec<StatementResult>> {
+        let mut results = Vec::with_capacity(statements.len());
+        let mut failed = false;
+        for sql in statements {
+            if failed {
+                results.push(StatementResult::skipped(sql));
+                continue;
+            }
+            let result = self.execute(&sql, None).await?;
+            failed = stop_on_error && result.error.is_some();
+            results.push(StatementResult::new(sql, result));
+        }
+        Ok(results)
+    }
+
+    // Cheap round trip used to tell a dead connection from a failing statement
+    async fn ping(&self) -> Result<()> {
+        self.execute("SELECT 1", None).await.map(|_| ())
+    }
 }
@@ -15,6 +15,18 @@ pub enum DbError {
     #[error("SSH error: {0}")]
     Ssh(String),
 
+    #[error("SSH host key for {host} is not trusted yet ({fingerprint})")]
+    SshHostKeyUnknown { host: String, fingerprint: String },
+
+    #[error("SSH host key for {host} has changed ({fingerprint}), refusing to connect. This could be a man-in-the-middle attack")]
+    SshHostKeyChanged { host: String, fingerprint: String },
+
+    #[error("Proxy error: {0}")]
+    Proxy(String),
+
+    #[error("Connection is read-only")]
+    ReadOnly,
+
     #[error("Invalid identifier: {0}")]
     InvalidIdentifier(String),
 
@@ -7,11 +7,13 @@ mod storage;
 use commands::*;
 use db::DatabaseManager;
 use storage::StorageService;
+use tauri::Emitter;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let storage = StorageService::new();
     let db = DatabaseManager::new();
+    let events = db.clone();
     let app_state = TauriState { storage, db };
 
     tauri::Builder::default()
@@ -19,13 +21,32 @@ pub fn run() {
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_log::Builder::default().build())
         .manage(app_state)
+        .setup(move |app| {
+            let handle = app.handle().clone();
+            events.set_event_handler(move |event| {
+                let _ = handle.emit("connection-state", event);
+            });
+            Ok(())
+        })
         .invoke_handler(tauri::generate_handler![
             connect,
             disconnect,
             test_connection,
+            scan_ssh_host_keys,
+            trust_ssh_host_key,
+            get_tunnel_status,
+            list_ssh_tunnels,
             get_connections,
             save_connection,
+            parse_connection_string,
+            get_connection_string,
+            import_client_connections,
             delete_connection,
+            move_connection,
+            get_folders,
+            save_folder,
+            move_folder,
+            delete_folder,
             get_settings,
             save_settings,
             get_state,
@@ -34,6 +55,8 @@ pub fn run() {
             get_history,
             save_history,
             query,
+            query_stream,
+            execute_script,
             export_query,
             cancel_query,
             get_tables,
@@ -7,9 +7,67 @@ pub enum DatabaseDriver {
     #[default]
     Postgres,
     Clickhouse,
+    Sqlite,
+    Duckdb,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+impl DatabaseDriver {
+    // Drivers that open a local file instead of talking to a server over the network.
+    pub fn is_file_based(&self) -> bool {
+        matches!(self, DatabaseDriver::Sqlite | DatabaseDriver::Duckdb)
+    }
+}
+
+// Prod connections ask for confirmation before running destructive statements
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Environment {
+    #[default]
+    Dev,
+    Staging,
+    Prod,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum SslMode {
+    Disable,
+    #[default]
+    Prefer,
+    Require,
+    VerifyCa,
+    VerifyFull,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyType {
+    #[default]
+    Socks5,
+    Http,
+}
+
+// An intermediate SSH server. Jump hosts are listed from the one closest to the app
+// to the one right before `ssh_host`, each reached through the previous one.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SshJumpHost {
+    pub host: String,
+    #[serde(default, deserialize_with = "deserialize_option_port")]
+    pub port: Option<u16>,
+    pub user: Option<String>,
+    pub password: Option<String>,
+    pub key_path: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionVariable {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct ConnectionConfig {
     pub id: String,
@@ -23,6 +81,16 @@ pub struct ConnectionConfig {
     pub password: Option<String>,
     pub database: String,
     pub exclude_list: Option<String>,
+    // Enforced by the database session, not only by hiding edit controls
+    pub read_only: Option<bool>,
+    pub environment: Option<Environment>,
+    // Sidebar organisation only, none of it affects connecting. Missing in files
+    // written before folders existed.
+    pub folder_id: Option<String>,
+    pub color: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+    pub sort_order: Option<u32>,
     pub use_ssh: Option<bool>,
     pub ssh_host: Option<String>,
     #[serde(default, deserialize_with = "deserialize_option_port")]
@@ -30,6 +98,33 @@ pub struct ConnectionConfig {
     pub ssh_user: Option<String>,
     pub ssh_password: Option<String>,
     pub ssh_key_path: Option<String>,
+    pub ssh_key_passphrase: Option<String>,
+    // Private key pasted into the editor, used before any key file
+    pub ssh_private_key: Option<String>,
+    pub ssh_jump_hosts: Option<Vec<SshJumpHost>>,
+    pub use_proxy: Option<bool>,
+    pub proxy_type: Option<ProxyType>,
+    pub proxy_host: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_option_port")]
+    pub proxy_port: Option<u16>,
+    pub proxy_user: Option<String>,
+    pub proxy_password: Option<String>,
+    pub ssl_mode: Option<SslMode>,
+    pub ssl_ca_path: Option<String>,
+    pub ssl_cert_path: Option<String>,
+    pub ssl_key_path: Option<String>,
+    pub ssl_accept_invalid_certs: Option<bool>,
+    pub connect_timeout: Option<u64>,
+    pub application_name: Option<String>,
+    // Pool and timeout tuning, timeouts are in seconds
+    pub max_connections: Option<u32>,
+    pub acquire_timeout: Option<u64>,
+    pub idle_timeout: Option<u64>,
+    pub statement_timeout: Option<u64>,
+    pub http_timeout: Option<u64>,
+    // Applied to every new pooled connection, variables first
+    pub session_variables: Option<Vec<SessionVariable>>,
+    pub init_script: Option<String>,
 }
 
 fn deserialize_port<'de, D>(deserializer: D) -> Result<u16, D::Error>
@@ -86,11 +181,40 @@ pub struct ConnectionSummary {
     pub user: String,
     pub database: String,
     pub exclude_list: Option<String>,
+    pub read_only: Option<bool>,
+    pub environment: Option<Environment>,
+    pub folder_id: Option<String>,
+    pub color: Option<String>,
+    pub tags: Option<Vec<String>>,
+    pub notes: Option<String>,
+    pub sort_order: Option<u32>,
     pub use_ssh: Option<bool>,
     pub ssh_host: Option<String>,
     pub ssh_port: Option<u16>,
     pub ssh_user: Option<String>,
     pub ssh_key_path: Option<String>,
+    pub has_ssh_private_key: bool,
+    pub has_ssh_key_passphrase: bool,
+    pub ssh_jump_hosts: Option<Vec<SshJumpHost>>,
+    pub use_proxy: Option<bool>,
+    pub proxy_type: Option<ProxyType>,
+    pub proxy_host: Option<String>,
+    pub proxy_port: Option<u16>,
+    pub proxy_user: Option<String>,
+    pub ssl_mode: Option<SslMode>,
+    pub ssl_ca_path: Option<String>,
+    pub ssl_cert_path: Option<String>,
+    pub ssl_key_path: Option<String>,
+    pub ssl_accept_invalid_certs: Option<bool>,
+    pub connect_timeout: Option<u64>,
+    pub application_name: Option<String>,
+    pub max_connections: Option<u32>,
+    pub acquire_timeout: Option<u64>,
+    pub idle_timeout: Option<u64>,
+    pub statement_timeout: Option<u64>,
+    pub http_timeout: Option<u64>,
+    pub session_variables: Option<Vec<SessionVariable>>,
+    pub init_script: Option<String>,
 }
 
 impl From<ConnectionConfig> for ConnectionSummary {
@@ -104,11 +228,75 @@ impl From<ConnectionConfig> for ConnectionSummary {
             user: c.user,
             database: c.database,
             exclude_list: c.exclude_list,
+            read_only: c.read_only,
+            environment: c.environment,
+            folder_id: c.folder_id,
+            color: c.color,
+            tags: c.tags,
+            notes: c.notes,
+            sort_order: c.sort_order,
             use_ssh: c.use_ssh,
             ssh_host: c.ssh_host,
             ssh_port: c.ssh_port,
             ssh_user: c.ssh_user,
             ssh_key_path: c.ssh_key_path,
+            has_ssh_private_key: c.ssh_private_key.is_some_and(|k| !k.is_empty()),
+            has_ssh_key_passphrase: c.ssh_key_passphrase.is_some_and(|p| !p.is_empty()),
+            ssh_jump_hosts: c.ssh_jump_hosts.map(|hosts| {
+                hosts
+                    .into_iter()
+                    .map(|h| SshJumpHost { password: None, ..h })
+                    .collect()
+            }),
+            use_proxy: c.use_proxy,
+            proxy_type: c.proxy_type,
+            proxy_host: c.proxy_host,
+            proxy_port: c.proxy_port,
+            proxy_user: c.proxy_user,
+            ssl_mode: c.ssl_mode,
+            ssl_ca_path: c.ssl_ca_path,
+            ssl_cert_path: c.ssl_cert_path,
+            ssl_key_path: c.ssl_key_path,
+            ssl_accept_invalid_certs: c.ssl_accept_invalid_certs,
+            connect_timeout: c.connect_timeout,
+            application_name: c.application_name,
+            max_connections: c.max_connections,
+            acquire_timeout: c.acquire_timeout,
+            idle_timeout: c.idle_timeout,
+            statement_timeout: c.statement_timeout,
+            http_timeout: c.http_timeout,
+            session_variables: c.session_variables,
+            init_script: c.init_script,
         }
     }
 }
+
+// A sidebar folder. Folders nest through `parent_id` and are stored apart from the
+// connections, so empty ones survive.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionFolder {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub color: Option<String>,
+    pub sort_order: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum ConnectionState {
+    Reconnecting,
+    Connected,
+    Failed,
+}
+
+// Sent to the frontend while a dropped connection is being restored
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionEvent {
+    pub connection_id: String,
+    pub state: ConnectionState,
+    pub attempt: u32,
+    pub error: Option<String>,
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -12,6 +13,9 @@ pub struct DashboardMetrics {
     pub table_count: i32,
     pub cache_hit_ratio: f64,
     pub top_queries: Vec<TopQuery>,
+    // Driver-specific figures that don't fit the common fields (e.g. SQLite journal mode).
+    #[serde(default)]
+    pub extra: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -1,11 +1,14 @@
 mod connection;
+mod dsn;
 mod metrics;
 mod query;
 mod schema;
 mod settings;
+mod ssh;
 
 pub use connection::*;
 pub use metrics::*;
 pub use query::*;
 pub use schema::*;
 pub use settings::*;
+pub use ssh::*;
@@ -4,10 +4,72 @@ use std::collections::HashMap;
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct QueryResult {
-    pub rows: Vec<HashMap<String, serde_json::Value>>,
-    pub columns: Vec<String>,
+    // One value per column, in the order of `columns`. Empty for streamed queries,
+    // whose rows go out in batches.
+    pub rows: Vec<Vec<serde_json::Value>>,
+    pub columns: Vec<ColumnInfo>,
     pub error: Option<String>,
     pub duration: f64,
+    // Set instead of rows when the statement was held back until the user confirms it
+    pub confirmation: Option<ConfirmationRequest>,
+    // Rows changed by INSERT/UPDATE/DELETE, or written by a ClickHouse query
+    pub rows_affected: Option<u64>,
+    // AUTO_INCREMENT value generated by a MySQL INSERT
+    pub last_insert_id: Option<u64>,
+    // Postgres only: a command tag like `UPDATE 42` or `CREATE TABLE`, rebuilt from the
+    // statement because sqlx doesn't expose the one the server sent
+    pub synthetic_command_tag: Option<String>,
+    // A streamed query stopped at the row cap with rows left unread
+    pub truncated: bool,
+}
+
+// Part of the rows of a streamed query
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RowBatch {
+    // Only on the first batch. Final metadata comes with the query result.
+    pub columns: Option<Vec<ColumnInfo>>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnInfo {
+    pub name: String,
+    // As the database reports it, e.g. `int4` or `Nullable(DateTime64(3))`
+    pub type_name: String,
+    pub logical_type: LogicalType,
+    // None when the driver can't tell, e.g. for computed columns
+    pub nullable: Option<bool>,
+    // Where the value comes from, for columns read straight from a table
+    pub source_table: Option<String>,
+    pub source_column: Option<String>,
+}
+
+// Database types grouped by how the UI should display and edit them
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum LogicalType {
+    Boolean,
+    Integer,
+    Decimal,
+    Float,
+    Text,
+    Json,
+    Uuid,
+    Date,
+    Time,
+    Timestamp,
+    Binary,
+    Array,
+    Other,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfirmationRequest {
+    pub token: String,
+    pub reasons: Vec<String>,
 }
 
 impl QueryResult {
@@ -17,17 +79,96 @@ impl QueryResult {
             columns: Vec::new(),
             error: None,
             duration: 0.0,
+            confirmation: None,
+            rows_affected: None,
+            last_insert_id: None,
+            synthetic_command_tag: None,
+            truncated: false,
         }
     }
 
     pub fn with_error(error: String, duration: f64) -> Self {
         QueryResult {
-            rows: Vec::new(),
-            columns: Vec::new(),
             error: Some(error),
             duration,
+            ..Self::empty()
+        }
+    }
+
+    // Column names with repeats numbered (`id`, `id_2`), for formats that key values
+    // by name
+    pub fn unique_column_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = Vec::with_capacity(self.columns.len());
+        for column in self.columns.iter().map(|c| &c.name) {
+            let mut name = column.clone();
+            let mut n = 1;
+            while names.contains(&name) {
+                n += 1;
+                name = format!("{}_{}", column, n);
+            }
+            names.push(name);
         }
+        names
     }
+
+    pub fn confirmation_required(confirmation: ConfirmationRequest) -> Self {
+        QueryResult {
+            confirmation: Some(confirmation),
+            ..Self::empty()
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum StatementStatus {
+    Success,
+    Error,
+    // Not run because an earlier statement failed
+    Skipped,
+}
+
+// Outcome of one statement of a script
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatementResult {
+    pub sql: String,
+    pub status: StatementStatus,
+    pub duration: f64,
+    pub result: QueryResult,
+}
+
+impl StatementResult {
+    pub fn new(sql: String, result: QueryResult) -> Self {
+        let status = if result.error.is_some() {
+            StatementStatus::Error
+        } else {
+            StatementStatus::Success
+        };
+        StatementResult {
+            sql,
+            status,
+            duration: result.duration,
+            result,
+        }
+    }
+
+    pub fn skipped(sql: String) -> Self {
+        StatementResult {
+            sql,
+            status: StatementStatus::Skipped,
+            duration: 0.0,
+            result: QueryResult::empty(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptResult {
+    pub statements: Vec<StatementResult>,
+    // Set instead of statements when the script was held back until the user confirms it
+    pub confirmation: Option<ConfirmationRequest>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -6,6 +6,13 @@ pub struct AppSettings {
     pub theme: String,
     pub locale: String,
     pub font_size: i32,
+    // Rows a query may return before the rest are cut off
+    #[serde(default = "default_max_result_rows")]
+    pub max_result_rows: usize,
+}
+
+fn default_max_result_rows() -> usize {
+    100_000
 }
 
 impl Default for AppSettings {
@@ -14,6 +21,7 @@ impl Default for AppSettings {
             theme: "atom-one-dark".to_string(),
             locale: "en".to_string(),
             font_size: 14,
+            max_result_rows: default_max_result_rows(),
         }
     }
 }
@@ -0,0 +1,43 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum HostKeyStatus {
+    Trusted,
+    Unknown,
+    Changed,
+}
+
+// Host key presented by one SSH hop, as shown to the user for confirmation.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SshHostKey {
+    pub host: String,
+    pub port: u16,
+    pub key_type: String,
+    pub fingerprint: String,
+    pub status: HostKeyStatus,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum TunnelState {
+    Connecting,
+    Connected,
+    Reconnecting,
+    Failed,
+    #[default]
+    Closed,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TunnelStatus {
+    pub state: TunnelState,
+    pub local_port: u16,
+    pub active_channels: usize,
+    pub reconnects: u32,
+    pub bytes_sent: u64,
+    pub bytes_received: u64,
+    pub last_error: Option<String>,
+}
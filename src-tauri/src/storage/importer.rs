@@ -0,0 +1,502 @@
+use crate::error::{DbError, Result};
+use crate::models::{ConnectionConfig, DatabaseDriver, SslMode};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// Where libpq looks for .pgpass and pg_service.conf, honouring PGPASSFILE and
+// PGSERVICEFILE like psql does
+pub fn pg_file_paths(home: &Path) -> (PathBuf, PathBuf) {
+    let pgpass = std::env::var_os("PGPASSFILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".pgpass"));
+    let service = std::env::var_os("PGSERVICEFILE")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| home.join(".pg_service.conf"));
+    (pgpass, service)
+}
+
+// Reads the config files of the standard CLI clients (psql, mysql, clickhouse-client)
+// found under `home` and turns every entry into a connection. Missing or unreadable
+// files are skipped, a broken file never aborts the whole import.
+pub fn discover_client_connections(
+    home: &Path,
+    pgpass_path: &Path,
+    service_path: &Path,
+) -> Vec<ConnectionConfig> {
+    let mut result = Vec::new();
+
+    let pgpass = read_optional(pgpass_path)
+        .map(|content| parse_pgpass(&content))
+        .unwrap_or_default();
+
+    if let Some(content) = read_optional(service_path) {
+        let mut services = parse_pg_service(&content);
+        for service in &mut services {
+            // libpq falls back to .pgpass when the service has no password
+            if service.password.is_none() {
+                service.password = pgpass_lookup(&pgpass, service);
+            }
+        }
+        result.extend(services);
+    }
+
+    // Wildcard lines only serve password lookups
+    for mut entry in pgpass {
+        if entry.host.is_empty() || entry.database.is_empty() || entry.user.is_empty() {
+            continue;
+        }
+        if entry.port == 0 {
+            entry.port = 5432;
+        }
+        result.push(entry);
+    }
+
+    if let Some(content) = read_optional(&home.join(".my.cnf")) {
+        result.extend(parse_my_cnf(&content));
+    }
+
+    let ch_dir = home.join(".clickhouse-client");
+    if let Some(content) = read_optional(&ch_dir.join("config.xml")) {
+        match parse_clickhouse_xml(&content) {
+            Ok(list) => result.extend(list),
+            Err(e) => log::warn!("Skipping clickhouse-client config.xml: {}", e),
+        }
+    }
+    for name in ["config.yaml", "config.yml"] {
+        if let Some(content) = read_optional(&ch_dir.join(name)) {
+            match parse_clickhouse_yaml(&content) {
+                Ok(list) => result.extend(list),
+                Err(e) => log::warn!("Skipping clickhouse-client {}: {}", name, e),
+            }
+        }
+    }
+
+    result
+}
+
+// Two connections point to the same place when they share driver, endpoint, user and database.
+pub fn is_same_target(a: &ConnectionConfig, b: &ConnectionConfig) -> bool {
+    a.driver == b.driver
+        && a.host.eq_ignore_ascii_case(&b.host)
+        && a.port == b.port
+        && a.user == b.user
+        && a.database == b.database
+}
+
+fn read_optional(path: &Path) -> Option<String> {
+    if !path.is_file() {
+        return None;
+    }
+    match fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) => {
+            log::warn!("Failed to read {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
+fn new_connection(driver: DatabaseDriver, name: String) -> ConnectionConfig {
+    ConnectionConfig {
+        id: uuid::Uuid::new_v4().to_string(),
+        driver,
+        name,
+        ..Default::default()
+    }
+}
+
+fn non_empty(value: Option<&str>) -> Option<String> {
+    value.filter(|v| !v.is_empty()).map(|v| v.to_string())
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(value.to_lowercase().as_str(), "1" | "true" | "yes" | "on")
+}
+
+fn parse_ssl_mode(value: &str) -> Option<SslMode> {
+    match value.to_lowercase().replace('_', "-").as_str() {
+        "disable" | "disabled" => Some(SslMode::Disable),
+        "allow" | "prefer" | "preferred" => Some(SslMode::Prefer),
+        "require" | "required" => Some(SslMode::Require),
+        "verify-ca" => Some(SslMode::VerifyCa),
+        "verify-full" | "verify-identity" => Some(SslMode::VerifyFull),
+        _ => None,
+    }
+}
+
+// Minimal INI reader shared by pg_service.conf and .my.cnf. Keys are normalized to
+// lowercase with `-` replaced by `_`, surrounding quotes are stripped from values.
+fn parse_ini(content: &str) -> Vec<(String, Vec<(String, String)>)> {
+    let mut sections: Vec<(String, Vec<(String, String)>)> = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty()
+            || line.starts_with('#')
+            || line.starts_with(';')
+            || line.starts_with('!')
+        {
+            continue;
+        }
+
+        if line.starts_with('[') && line.ends_with(']') {
+            sections.push((line[1..line.len() - 1].trim().to_string(), Vec::new()));
+            continue;
+        }
+
+        let Some((_, entries)) = sections.last_mut() else {
+            continue;
+        };
+        let (key, value) = match line.split_once('=') {
+            Some((k, v)) => (k.trim(), v.trim()),
+            None => (line, ""),
+        };
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .or_else(|| value.strip_prefix('\'').and_then(|v| v.strip_suffix('\'')))
+            .unwrap_or(value);
+
+        entries.push((key.to_lowercase().replace('-', "_"), value.to_string()));
+    }
+
+    sections
+}
+
+// Splits a .pgpass line on unescaped colons, resolving `\:` and `\\`.
+fn split_pgpass_line(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => {
+                if let Some(next) = chars.next() {
+                    fields.last_mut().unwrap().push(next);
+                }
+            }
+            ':' => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+// Wildcards are kept as an empty host/database/user or port 0 so the line can still
+// answer password lookups; lines with any wildcard but the port are not imported.
+pub fn parse_pgpass(content: &str) -> Vec<ConnectionConfig> {
+    let mut result = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let fields = split_pgpass_line(line);
+        if fields.len() != 5 {
+            log::warn!("Skipping malformed .pgpass line");
+            continue;
+        }
+
+        let host = if fields[0] == "*" {
+            String::new()
+        } else {
+            fields[0].clone()
+        };
+        let port = if fields[1] == "*" {
+            0
+        } else {
+            fields[1].parse().unwrap_or(5432)
+        };
+        let database = if fields[2] == "*" {
+            String::new()
+        } else {
+            fields[2].clone()
+        };
+        let user = if fields[3] == "*" {
+            String::new()
+        } else {
+            fields[3].clone()
+        };
+
+        let mut config = new_connection(
+            DatabaseDriver::Postgres,
+            format!("{}@{}/{}", fields[3], host, database),
+        );
+        config.host = host;
+        config.port = port;
+        config.database = database;
+        config.user = user;
+        config.password = non_empty(Some(&fields[4]));
+        result.push(config);
+    }
+
+    result
+}
+
+fn pgpass_lookup(entries: &[ConnectionConfig], target: &ConnectionConfig) -> Option<String> {
+    entries
+        .iter()
+        .find(|e| {
+            (e.host.is_empty() || e.host.eq_ignore_ascii_case(&target.host))
+                && (e.port == 0 || e.port == target.port)
+                && (e.database.is_empty() || e.database == target.database)
+                && (e.user.is_empty() || e.user == target.user)
+        })
+        .and_then(|e| e.password.clone())
+}
+
+pub fn parse_pg_service(content: &str) -> Vec<ConnectionConfig> {
+    parse_ini(content)
+        .into_iter()
+        .map(|(service, entries)| {
+            let mut config = new_connection(DatabaseDriver::Postgres, service);
+            config.host = "localhost".to_string();
+            config.port = 5432;
+
+            for (key, value) in entries {
+                match key.as_str() {
+                    "host" | "hostaddr" => config.host = value,
+                    "port" => config.port = value.parse().unwrap_or(5432),
+                    "dbname" => config.database = value,
+                    "user" => config.user = value,
+                    "password" => config.password = non_empty(Some(&value)),
+                    "sslmode" => config.ssl_mode = parse_ssl_mode(&value),
+                    "sslrootcert" => config.ssl_ca_path = non_empty(Some(&value)),
+                    "sslcert" => config.ssl_cert_path = non_empty(Some(&value)),
+                    "sslkey" => config.ssl_key_path = non_empty(Some(&value)),
+                    "connect_timeout" => config.connect_timeout = value.parse().ok(),
+                    "application_name" => config.application_name = non_empty(Some(&value)),
+                    _ => {}
+                }
+            }
+
+            if config.database.is_empty() {
+                config.database = config.user.clone();
+            }
+            config
+        })
+        .collect()
+}
+
+// The mysql client reads [client] first and lets [mysql] override it, so both are
+// merged into one connection. Other sections belong to server tools and are ignored.
+pub fn parse_my_cnf(content: &str) -> Vec<ConnectionConfig> {
+    let mut found = false;
+    let mut config = new_connection(DatabaseDriver::Mysql, String::new());
+    config.host = "localhost".to_string();
+    config.port = 3306;
+
+    for (section, entries) in parse_ini(content) {
+        if section != "client" && section != "mysql" {
+            continue;
+        }
+        found = true;
+
+        for (key, value) in entries {
+            match key.as_str() {
+                "host" => config.host = value,
+                "port" => config.port = value.parse().unwrap_or(3306),
+                "user" => config.user = value,
+                "password" => config.password = non_empty(Some(&value)),
+                "database" => config.database = value,
+                "ssl_mode" => config.ssl_mode = parse_ssl_mode(&value),
+                "ssl_ca" => config.ssl_ca_path = non_empty(Some(&value)),
+                "ssl_cert" => config.ssl_cert_path = non_empty(Some(&value)),
+                "ssl_key" => config.ssl_key_path = non_empty(Some(&value)),
+                "connect_timeout" => config.connect_timeout = value.parse().ok(),
+                _ => {}
+            }
+        }
+    }
+
+    if !found {
+        return Vec::new();
+    }
+    config.name = format!("MySQL {}", config.host);
+    vec![config]
+}
+
+// clickhouse-client talks the native protocol (9000/9440) while we use HTTP, so the
+// configured port is not reusable and the HTTP defaults are picked from `secure`.
+fn clickhouse_connection(
+    name: Option<String>,
+    get: impl Fn(&str) -> Option<String>,
+) -> ConnectionConfig {
+    let host = get("hostname")
+        .or_else(|| get("host"))
+        .unwrap_or_else(|| "localhost".to_string());
+    let secure = get("secure").map(|v| parse_flag(&v)).unwrap_or(false);
+
+    let mut config = new_connection(
+        DatabaseDriver::Clickhouse,
+        name.unwrap_or_else(|| format!("ClickHouse {}", host)),
+    );
+    config.host = host;
+    config.port = if secure { 8443 } else { 8123 };
+    config.user = get("user").unwrap_or_else(|| "default".to_string());
+    config.password = get("password").filter(|p| !p.is_empty());
+    config.database = get("database").unwrap_or_default();
+    if secure {
+        config.ssl_mode = Some(SslMode::VerifyFull);
+    }
+    config
+}
+
+pub fn parse_clickhouse_xml(content: &str) -> Result<Vec<ConnectionConfig>> {
+    let doc = roxmltree::Document::parse(content)
+        .map_err(|e| DbError::Config(format!("Invalid XML: {}", e)))?;
+    let root = doc.root_element();
+
+    let child_text = |node: roxmltree::Node, tag: &str| -> Option<String> {
+        node.children()
+            .find(|n| n.has_tag_name(tag))
+            .and_then(|n| n.text())
+            .map(|t| t.trim().to_string())
+    };
+
+    let mut result = Vec::new();
+    if root.children().any(|n| n.has_tag_name("host")) {
+        result.push(clickhouse_connection(None, |tag| child_text(root, tag)));
+    }
+
+    for credentials in root
+        .children()
+        .filter(|n| n.has_tag_name("connections_credentials"))
+    {
+        for conn in credentials
+            .children()
+            .filter(|n| n.has_tag_name("connection"))
+        {
+            let name = child_text(conn, "name");
+            result.push(clickhouse_connection(name, |tag| child_text(conn, tag)));
+        }
+    }
+
+    Ok(result)
+}
+
+pub fn parse_clickhouse_yaml(content: &str) -> Result<Vec<ConnectionConfig>> {
+    use serde_yaml::Value;
+
+    let doc: Value = serde_yaml::from_str(content)
+        .map_err(|e| DbError::Config(format!("Invalid YAML: {}", e)))?;
+
+    let field = |node: &Value, key: &str| -> Option<String> {
+        match node.get(key)? {
+            Value::String(s) => Some(s.clone()),
+            Value::Number(n) => Some(n.to_string()),
+            Value::Bool(b) => Some(b.to_string()),
+            _ => None,
+        }
+    };
+
+    let mut result = Vec::new();
+    if doc.get("host").is_some() {
+        result.push(clickhouse_connection(None, |key| field(&doc, key)));
+    }
+
+    // `connection` is a single mapping or a list, depending on how many are defined
+    let connections = doc
+        .get("connections_credentials")
+        .and_then(|c| c.get("connection"));
+    let connections: Vec<&Value> = match connections {
+        Some(Value::Sequence(list)) => list.iter().collect(),
+        Some(single @ Value::Mapping(_)) => vec![single],
+        _ => Vec::new(),
+    };
+    for conn in connections {
+        let name = field(conn, "name");
+        result.push(clickhouse_connection(name, |key| field(conn, key)));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_parse_pgpass() {
+        let content = "# comment\n\
+            db.internal:5433:orders:app:p\\:ss\n\
+            *:*:*:admin:secret\n\
+            *:*:*:*:shared\n\
+            broken line\n";
+
+        let entries = parse_pgpass(content);
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].host, "db.internal");
+        assert_eq!(entries[0].port, 5433);
+        assert_eq!(entries[0].database, "orders");
+        assert_eq!(entries[0].password.as_deref(), Some("p:ss"));
+        assert!(entries[1].host.is_empty());
+        assert!(entries[2].user.is_empty());
+    }
+
+    #[test]
+    fn test_parse_pg_service_with_pgpass_fallback() {
+        let dir = tempdir().unwrap();
+        fs::write(
+            dir.path().join(".pg_service.conf"),
+            "[reporting]\nhost=replica.internal\nport=6432\ndbname=analytics\nuser=reader\nsslmode=verify-full\n\n[billing]\nhost=billing.internal\nuser=billing\n",
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join(".pgpass"),
+            "*:6432:*:reader:from-pgpass\n*:*:*:*:shared\n",
+        )
+        .unwrap();
+
+        let found = discover_client_connections(
+            dir.path(),
+            &dir.path().join(".pgpass"),
+            &dir.path().join(".pg_service.conf"),
+        );
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0].name, "reporting");
+        assert_eq!(found[0].host, "replica.internal");
+        assert_eq!(found[0].ssl_mode, Some(SslMode::VerifyFull));
+        assert_eq!(found[0].password.as_deref(), Some("from-pgpass"));
+        assert_eq!(found[1].password.as_deref(), Some("shared"));
+    }
+
+    #[test]
+    fn test_parse_my_cnf() {
+        let content = "[mysqld]\nport=3307\n\n[client]\nuser=root\npassword=\"s3cr#t\"\nhost=mysql.internal\n\n[mysql]\ndatabase=shop\nssl-mode=REQUIRED\n";
+
+        let found = parse_my_cnf(content);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].host, "mysql.internal");
+        assert_eq!(found[0].port, 3306);
+        assert_eq!(found[0].password.as_deref(), Some("s3cr#t"));
+        assert_eq!(found[0].database, "shop");
+        assert_eq!(found[0].ssl_mode, Some(SslMode::Require));
+        assert!(parse_my_cnf("[mysqld]\nport=3307\n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_clickhouse_configs() {
+        let xml = "<config>\
+            <user>default</user><host>localhost</host>\
+            <connections_credentials>\
+              <connection><name>prod</name><hostname>ch.example.com</hostname><port>9440</port>\
+              <secure>1</secure><user>analyst</user><password>pw</password></connection>\
+            </connections_credentials></config>";
+        let found = parse_clickhouse_xml(xml).unwrap();
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[1].name, "prod");
+        assert_eq!(found[1].port, 8443);
+        assert_eq!(found[1].ssl_mode, Some(SslMode::VerifyFull));
+
+        let yaml = "connections_credentials:\n  connection:\n    - name: staging\n      hostname: ch-stage\n      user: dev\n";
+        let found = parse_clickhouse_yaml(yaml).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].host, "ch-stage");
+        assert_eq!(found[0].port, 8123);
+
+        assert!(parse_clickhouse_xml("<config>").is_err());
+    }
+}
@@ -1,4 +1,5 @@
 mod encryption;
+mod importer;
 mod service;
 
 pub use service::StorageService;
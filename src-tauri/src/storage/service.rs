@@ -1,14 +1,16 @@
-use crate::error::Result;
+use crate::error::{DbError, Result};
 use super::encryption::EncryptionManager;
+use super::importer;
 use crate::models::{
-    AppSettings, AppStateData, ConnectionConfig, ConnectionSummary, HistoryItem,
+    AppSettings, AppStateData, ConnectionConfig, ConnectionFolder, ConnectionSummary, HistoryItem,
 };
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 
 const APP_DIR: &str = ".gridly";
 const CONNECTIONS_FILE: &str = "connections.json";
+const FOLDERS_FILE: &str = "folders.json";
 const SETTINGS_FILE: &str = "settings.json";
 const STATE_FILE: &str = "state.json";
 const HISTORY_FILE: &str = "history.json";
@@ -98,6 +100,26 @@ impl StorageService {
                         }
                     }
                 }
+
+                for jump in conn.ssh_jump_hosts.iter_mut().flatten() {
+                    if let Some(pwd) = &jump.password {
+                        if pwd.starts_with("ENC:") {
+                            jump.password = mgr.decrypt(pwd);
+                        }
+                    }
+                }
+
+                for secret in [
+                    &mut conn.ssh_key_passphrase,
+                    &mut conn.ssh_private_key,
+                    &mut conn.proxy_password,
+                ] {
+                    if let Some(value) = secret.as_deref() {
+                        if value.starts_with("ENC:") {
+                            *secret = mgr.decrypt(value);
+                        }
+                    }
+                }
             }
         }
 
@@ -120,6 +142,24 @@ impl StorageService {
                         conn.ssh_password = Some(mgr.encrypt(pwd));
                     }
                 }
+                for jump in conn.ssh_jump_hosts.iter_mut().flatten() {
+                    if let Some(pwd) = &jump.password {
+                        if !pwd.is_empty() && !pwd.starts_with("ENC:") {
+                            jump.password = Some(mgr.encrypt(pwd));
+                        }
+                    }
+                }
+                for secret in [
+                    &mut conn.ssh_key_passphrase,
+                    &mut conn.ssh_private_key,
+                    &mut conn.proxy_password,
+                ] {
+                    if let Some(value) = secret.as_deref() {
+                        if !value.is_empty() && !value.starts_with("ENC:") {
+                            *secret = Some(mgr.encrypt(value));
+                        }
+                    }
+                }
             }
         }
 
@@ -140,17 +180,29 @@ impl StorageService {
         if !path.exists() {
             return Vec::new();
         }
-        let connections: Vec<ConnectionConfig> = match fs::read_to_string(path) {
+        let mut connections: Vec<ConnectionConfig> = match fs::read_to_string(path) {
             Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
             Err(_) => Vec::new(),
         };
+        sort_by_order(&mut connections, |c| c.sort_order);
         
         connections.into_iter().map(ConnectionSummary::from).collect()
     }
 
     pub fn save_connection(&self, mut connection: ConnectionConfig) -> Result<()> {
         // Handle partial updates where password might be None (preserve existing)
-        if connection.password.is_none() || connection.ssh_password.is_none() {
+        let jump_password_missing = connection
+            .ssh_jump_hosts
+            .iter()
+            .flatten()
+            .any(|j| j.password.is_none());
+        if connection.password.is_none()
+            || connection.ssh_password.is_none()
+            || connection.ssh_key_passphrase.is_none()
+            || connection.ssh_private_key.is_none()
+            || connection.proxy_password.is_none()
+            || jump_password_missing
+        {
             if let Some(existing) = self.get_connection(&connection.id) {
                 if connection.password.is_none() {
                     connection.password = existing.password;
@@ -158,6 +210,25 @@ impl StorageService {
                 if connection.ssh_password.is_none() {
                     connection.ssh_password = existing.ssh_password;
                 }
+                if connection.ssh_key_passphrase.is_none() {
+                    connection.ssh_key_passphrase = existing.ssh_key_passphrase;
+                }
+                if connection.ssh_private_key.is_none() {
+                    connection.ssh_private_key = existing.ssh_private_key;
+                }
+                if connection.proxy_password.is_none() {
+                    connection.proxy_password = existing.proxy_password;
+                }
+                // Jump hosts keep their password as long as host and user are unchanged
+                let existing_jumps = existing.ssh_jump_hosts.unwrap_or_default();
+                for jump in connection.ssh_jump_hosts.iter_mut().flatten() {
+                    if jump.password.is_none() {
+                        jump.password = existing_jumps
+                            .iter()
+                            .find(|e| e.host == jump.host && e.user == jump.user)
+                            .and_then(|e| e.password.clone());
+                    }
+                }
             }
         }
 
@@ -172,12 +243,150 @@ impl StorageService {
         self.save_connections_internal(&connections)
     }
 
+    // Imports connections from the CLI client configs in the user's home directory.
+    // Entries matching an existing connection are skipped, returns what was added.
+    pub fn import_client_configs(&self) -> Result<Vec<ConnectionSummary>> {
+        let home = dirs::home_dir().unwrap_or_else(|| PathBuf::from("."));
+        let (pgpass, pg_service) = importer::pg_file_paths(&home);
+        self.import_client_configs_from(&home, &pgpass, &pg_service)
+    }
+
+    pub fn import_client_configs_from(
+        &self,
+        home: &Path,
+        pgpass: &Path,
+        pg_service: &Path,
+    ) -> Result<Vec<ConnectionSummary>> {
+        let mut connections = self.get_connections();
+        let mut imported = Vec::new();
+
+        for candidate in importer::discover_client_connections(home, pgpass, pg_service) {
+            if connections
+                .iter()
+                .any(|c| importer::is_same_target(c, &candidate))
+            {
+                continue;
+            }
+            imported.push(ConnectionSummary::from(candidate.clone()));
+            connections.push(candidate);
+        }
+
+        if !imported.is_empty() {
+            self.save_connections_internal(&connections)?;
+        }
+        Ok(imported)
+    }
+
     pub fn delete_connection(&self, id: &str) -> Result<()> {
         let mut connections = self.get_connections();
         connections.retain(|c| c.id != id);
         self.save_connections_internal(&connections)
     }
 
+    // Puts a connection into `folder_id` (the top level for None) at `index` among the
+    // connections already there, renumbering them
+    pub fn move_connection(&self, id: &str, folder_id: Option<String>, index: usize) -> Result<()> {
+        if let Some(folder_id) = &folder_id {
+            if !self.get_folders().iter().any(|f| &f.id == folder_id) {
+                return Err(DbError::Config(format!("Folder not found: {}", folder_id)));
+            }
+        }
+
+        let mut connections = self.get_connections();
+        let pos = connections
+            .iter()
+            .position(|c| c.id == id)
+            .ok_or_else(|| DbError::ConnectionNotFound(id.to_string()))?;
+        let siblings = connections
+            .iter()
+            .enumerate()
+            .filter(|(i, c)| *i != pos && c.folder_id == folder_id)
+            .map(|(i, c)| (i, c.sort_order))
+            .collect();
+        connections[pos].folder_id = folder_id;
+        for (i, order) in place(siblings, pos, index) {
+            connections[i].sort_order = Some(order);
+        }
+
+        self.save_connections_internal(&connections)
+    }
+
+    pub fn get_folders(&self) -> Vec<ConnectionFolder> {
+        let path = self.get_file_path(FOLDERS_FILE);
+        let mut folders: Vec<ConnectionFolder> = match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        sort_by_order(&mut folders, |f| f.sort_order);
+        folders
+    }
+
+    pub fn save_folder(&self, folder: ConnectionFolder) -> Result<()> {
+        let mut folders = self.get_folders();
+        check_parent(&folders, &folder.id, folder.parent_id.as_deref())?;
+
+        if let Some(pos) = folders.iter().position(|f| f.id == folder.id) {
+            folders[pos] = folder;
+        } else {
+            folders.push(folder);
+        }
+
+        self.save_json(FOLDERS_FILE, &folders)
+    }
+
+    pub fn move_folder(&self, id: &str, parent_id: Option<String>, index: usize) -> Result<()> {
+        let mut folders = self.get_folders();
+        let pos = folders
+            .iter()
+            .position(|f| f.id == id)
+            .ok_or_else(|| DbError::Config(format!("Folder not found: {}", id)))?;
+        check_parent(&folders, id, parent_id.as_deref())?;
+
+        let siblings = folders
+            .iter()
+            .enumerate()
+            .filter(|(i, f)| *i != pos && f.parent_id == parent_id)
+            .map(|(i, f)| (i, f.sort_order))
+            .collect();
+        folders[pos].parent_id = parent_id;
+        for (i, order) in place(siblings, pos, index) {
+            folders[i].sort_order = Some(order);
+        }
+
+        self.save_json(FOLDERS_FILE, &folders)
+    }
+
+    // Subfolders and connections of a deleted folder move up to its parent
+    pub fn delete_folder(&self, id: &str) -> Result<()> {
+        let mut folders = self.get_folders();
+        let Some(pos) = folders.iter().position(|f| f.id == id) else {
+            return Ok(());
+        };
+        let removed = folders.remove(pos);
+
+        for folder in &mut folders {
+            if folder.parent_id.as_deref() == Some(id) {
+                folder.parent_id = removed.parent_id.clone();
+                folder.sort_order = None;
+            }
+        }
+
+        let mut connections = self.get_connections();
+        let mut moved = false;
+        for conn in &mut connections {
+            if conn.folder_id.as_deref() == Some(id) {
+                conn.folder_id = removed.parent_id.clone();
+                conn.sort_order = None;
+                moved = true;
+            }
+        }
+        if moved {
+            self.save_connections_internal(&connections)?;
+        }
+
+        self.save_json(FOLDERS_FILE, &folders)
+    }
+
     pub fn get_settings(&self) -> AppSettings {
         let path = self.get_file_path(SETTINGS_FILE);
         if !path.exists() {
@@ -277,6 +486,45 @@ impl StorageService {
     }
 }
 
+// Stable, so entries that were never ordered keep their file order after the rest
+fn sort_by_order<T>(items: &mut [T], order: impl Fn(&T) -> Option<u32>) {
+    items.sort_by_key(|item| order(item).unwrap_or(u32::MAX));
+}
+
+// New orders for the entries of one folder once `moved` is inserted at `index` among
+// `siblings`, given as (position in the list, current order)
+fn place(mut siblings: Vec<(usize, Option<u32>)>, moved: usize, index: usize) -> Vec<(usize, u32)> {
+    siblings.sort_by_key(|(i, order)| (order.unwrap_or(u32::MAX), *i));
+    let mut ordered: Vec<usize> = siblings.into_iter().map(|(i, _)| i).collect();
+    ordered.insert(index.min(ordered.len()), moved);
+    ordered.into_iter().zip(0..).collect()
+}
+
+// The parent has to exist and must not be the folder itself or one of its descendants
+fn check_parent(folders: &[ConnectionFolder], id: &str, parent_id: Option<&str>) -> Result<()> {
+    let mut current = parent_id;
+    // Bounded in case the file already contains a cycle
+    for _ in 0..=folders.len() {
+        let Some(parent) = current else {
+            return Ok(());
+        };
+        if parent == id {
+            return Err(DbError::Config(
+                "A folder can't be moved into itself".to_string(),
+            ));
+        }
+        current = folders
+            .iter()
+            .find(|f| f.id == parent)
+            .ok_or_else(|| DbError::Config(format!("Folder not found: {}", parent)))?
+            .parent_id
+            .as_deref();
+    }
+    Err(DbError::Config(
+        "Folder hierarchy contains a cycle".to_string(),
+    ))
+}
+
 impl Default for StorageService {
     fn default() -> Self {
         Self::new()
@@ -297,6 +545,7 @@ mod tests {
             theme: "test-theme".to_string(),
             locale: "fr".to_string(),
             font_size: 20,
+            max_result_rows: 5_000,
         };
 
         let result = service.save_settings(settings.clone());
@@ -306,6 +555,7 @@ mod tests {
         assert_eq!(loaded.theme, "test-theme");
         assert_eq!(loaded.locale, "fr");
         assert_eq!(loaded.font_size, 20);
+        assert_eq!(loaded.max_result_rows, 5_000);
     }
 
     #[test]
@@ -329,6 +579,7 @@ mod tests {
             ssh_user: None,
             ssh_password: None,
             ssh_key_path: None,
+            ..Default::default()
         };
 
         service.save_connection(conn.clone()).unwrap();
@@ -351,4 +602,62 @@ mod tests {
         service.delete_connection("conn1").unwrap();
         assert!(service.get_connections().is_empty());
     }
+
+    fn folder(id: &str, parent_id: Option<&str>) -> ConnectionFolder {
+        ConnectionFolder {
+            id: id.to_string(),
+            name: id.to_string(),
+            parent_id: parent_id.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_folders_move_and_delete() {
+        let dir = tempdir().unwrap();
+        let service = StorageService::new_with_path(dir.path().to_path_buf());
+
+        service.save_folder(folder("work", None)).unwrap();
+        service.save_folder(folder("prod", Some("work"))).unwrap();
+        service
+            .save_folder(folder("staging", Some("work")))
+            .unwrap();
+        assert!(service
+            .save_folder(folder("orphan", Some("missing")))
+            .is_err());
+
+        // Into its own subtree
+        assert!(service
+            .move_folder("work", Some("prod".to_string()), 0)
+            .is_err());
+
+        service
+            .move_folder("staging", Some("work".to_string()), 0)
+            .unwrap();
+        let ids: Vec<_> = service.get_folders().into_iter().map(|f| f.id).collect();
+        assert_eq!(ids, vec!["staging", "prod", "work"]);
+
+        service.delete_folder("work").unwrap();
+        let folders = service.get_folders();
+        assert_eq!(folders.len(), 2);
+        assert!(folders.iter().all(|f| f.parent_id.is_none()));
+    }
+
+    #[test]
+    fn test_place_renumbers_siblings() {
+        // Positions 4 and 2 are ordered, 7 never was
+        let siblings = vec![(4, Some(1)), (7, None), (2, Some(0))];
+        assert_eq!(place(siblings, 9, 1), vec![(2, 0), (9, 1), (4, 2), (7, 3)]);
+        assert_eq!(place(vec![], 3, 5), vec![(3, 0)]);
+    }
+
+    #[test]
+    fn test_connections_without_organisation_fields_still_load() {
+        let content = r#"[{"id":"c1","type":"postgres","name":"Old","host":"localhost",
+            "port":"5432","user":"me","password":null,"database":"app"}]"#;
+        let connections: Vec<ConnectionConfig> = serde_json::from_str(content).unwrap();
+        assert_eq!(connections[0].folder_id, None);
+        assert_eq!(connections[0].tags, None);
+        assert_eq!(connections[0].sort_order, None);
+    }
 }
@@ -25,6 +25,7 @@ async fn test_mysql_all_types() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let res = manager.connect(conn_id.to_string(), config).await;
@@ -172,6 +173,7 @@ async fn test_postgres_all_types() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let res = manager.connect(conn_id.to_string(), config).await;
@@ -340,6 +342,7 @@ async fn test_clickhouse_all_types() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let mut result = manager.connect(conn_id.to_string(), config.clone()).await;
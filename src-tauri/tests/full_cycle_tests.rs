@@ -25,6 +25,7 @@ async fn test_postgres_full_cycle() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let result = manager.connect(conn_id.clone(), config).await;
@@ -140,6 +141,7 @@ async fn test_mysql_full_cycle() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let result = manager.connect(conn_id.clone(), config).await;
@@ -246,6 +248,7 @@ async fn test_clickhouse_full_cycle() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let mut result = manager.connect(conn_id.clone(), config.clone()).await;
@@ -1,5 +1,5 @@
 use app_lib::db::DatabaseManager;
-use app_lib::models::{ConnectionConfig, DatabaseDriver};
+use app_lib::models::{ConnectionConfig, DatabaseDriver, StatementStatus};
 
 #[tokio::test]
 async fn test_real_postgres_connection() {
@@ -20,6 +20,7 @@ async fn test_real_postgres_connection() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let result = manager.connect("test_pg".to_string(), config).await;
@@ -55,6 +56,7 @@ async fn test_real_mysql_connection() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let result = manager.connect("test_mysql".to_string(), config).await;
@@ -66,6 +68,58 @@ async fn test_real_mysql_connection() {
     assert!(tables.contains(&"users".to_string()));
 }
 
+#[tokio::test]
+async fn test_mysql_script_with_procedure() {
+    let manager = DatabaseManager::new();
+    let config = ConnectionConfig {
+        id: "test_mysql_script".to_string(),
+        name: "Test MySQL Script".to_string(),
+        driver: DatabaseDriver::Mysql,
+        host: "127.0.0.1".to_string(),
+        port: 33060,
+        user: "test_user".to_string(),
+        password: Some("test_password".to_string()),
+        database: "test_db".to_string(),
+        ..Default::default()
+    };
+    let id = "test_mysql_script".to_string();
+    let result = manager.connect(id.clone(), config).await;
+    assert!(result.is_ok(), "Failed to connect to MySQL: {:?}", result.err());
+
+    // Procedure bodies can't go through the prepared statement protocol
+    let script = "DROP PROCEDURE IF EXISTS script_test_add;
+DELIMITER //
+CREATE PROCEDURE script_test_add(IN a INT, IN b INT)
+BEGIN
+    DECLARE total INT;
+    SET total = a + b;
+    SELECT total;
+END //
+DELIMITER ;
+CALL script_test_add(2, 3);";
+    let result = manager
+        .execute_script(id.clone(), script.to_string(), true, None)
+        .await
+        .expect("Script failed");
+    assert_eq!(result.statements.len(), 3);
+    for statement in &result.statements {
+        assert_eq!(
+            statement.status,
+            StatementStatus::Success,
+            "{}: {:?}",
+            statement.sql,
+            statement.result.error
+        );
+    }
+    let call = &result.statements[2].result;
+    assert_eq!(call.rows[0][0], serde_json::json!(5));
+
+    let _ = manager
+        .execute(id.clone(), "DROP PROCEDURE script_test_add".to_string(), None)
+        .await;
+    let _ = manager.disconnect(id).await;
+}
+
 // TODO: SSH Test is currently flaky due to Docker permission issues. Requires manual verification.
 // #[tokio::test]
 #[allow(dead_code)]
@@ -89,6 +143,7 @@ async fn test_ssh_tunnel_postgres() {
         ssh_user: Some("test_user".to_string()),
         ssh_password: Some("test_password".to_string()),
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let result = manager.connect("test_ssh_pg".to_string(), config).await;
@@ -117,6 +172,7 @@ async fn test_real_clickhouse_connection() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     let mut result = manager.connect("test_ch".to_string(), config.clone()).await;
@@ -45,6 +45,7 @@ async fn test_ssh_tunnel_with_retry() {
         ssh_user: Some("test_user".to_string()),
         ssh_password: Some("test_password".to_string()),
         ssh_key_path: None,
+        ..Default::default()
     };
 
     // Retry connection logic
@@ -91,6 +92,7 @@ async fn test_user_session_workflow() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     manager.connect(conn_id.clone(), config).await.expect("Connect failed");
@@ -181,6 +183,7 @@ async fn test_multi_connection_workflow() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     // Connection B (MySQL)
@@ -201,6 +204,7 @@ async fn test_multi_connection_workflow() {
         ssh_user: None,
         ssh_password: None,
         ssh_key_path: None,
+        ..Default::default()
     };
 
     // 1. Open Both
@@ -0,0 +1,211 @@
+use app_lib::db::ssh::{self, KnownHostsStore};
+use app_lib::db::DatabaseManager;
+use app_lib::error::DbError;
+use app_lib::models::{ConnectionConfig, DatabaseDriver, HostKeyStatus, TunnelState};
+use std::time::{Duration, Instant};
+
+// Postgres as seen from the ssh-server container of docker-compose, which stands in
+// for a bastion host
+fn postgres_config(id: &str, use_ssh: bool) -> ConnectionConfig {
+    let (host, port) = if use_ssh {
+        ("postgres", 5432)
+    } else {
+        ("127.0.0.1", 54320)
+    };
+    ConnectionConfig {
+        id: id.to_string(),
+        name: id.to_string(),
+        driver: DatabaseDriver::Postgres,
+        host: host.to_string(),
+        port,
+        user: "test_user".to_string(),
+        password: Some("test_password".to_string()),
+        database: "test_db".to_string(),
+        use_ssh: Some(use_ssh),
+        ssh_host: Some("127.0.0.1".to_string()),
+        ssh_port: Some(2222),
+        ssh_user: Some("test_user".to_string()),
+        ssh_password: Some("test_password".to_string()),
+        ..Default::default()
+    }
+}
+
+// Host keys are trusted into a throwaway store, never into the user's known_hosts
+fn test_manager() -> (DatabaseManager, KnownHostsStore, tempfile::TempDir) {
+    let dir = tempfile::tempdir().unwrap();
+    let known_hosts = KnownHostsStore::with_paths(
+        dir.path().join("known_hosts"),
+        dir.path().join("user_known_hosts"),
+    );
+    let manager = DatabaseManager::new();
+    manager.set_known_hosts(known_hosts.clone());
+    (manager, known_hosts, dir)
+}
+
+fn trust_ssh_server(config: &ConnectionConfig, known_hosts: &KnownHostsStore) {
+    let keys = ssh::scan_host_keys(config, known_hosts).expect("Failed to scan SSH host keys");
+    for key in keys.iter().filter(|k| k.status != HostKeyStatus::Trusted) {
+        ssh::trust_host_key(config, known_hosts, &key.host, key.port, &key.fingerprint)
+            .expect("Failed to trust SSH host key");
+    }
+}
+
+async fn connect(
+    manager: &DatabaseManager,
+    known_hosts: &KnownHostsStore,
+    id: &str,
+    use_ssh: bool,
+) {
+    let config = postgres_config(id, use_ssh);
+    if use_ssh {
+        let to_trust = config.clone();
+        let known_hosts = known_hosts.clone();
+        tokio::task::spawn_blocking(move || trust_ssh_server(&to_trust, &known_hosts))
+            .await
+            .unwrap();
+    }
+    let res = manager.connect(id.to_string(), config).await;
+    assert!(res.is_ok(), "Failed to connect {}: {:?}", id, res.err());
+}
+
+async fn timed_query(manager: &DatabaseManager, id: &str, sql: &str) -> (Duration, usize) {
+    let start = Instant::now();
+    let res = manager
+        .execute(id.to_string(), sql.to_string(), None)
+        .await
+        .expect("Query failed");
+    let elapsed = start.elapsed();
+    let bytes = serde_json::to_string(&res.rows).unwrap().len();
+    (elapsed, bytes)
+}
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort();
+    samples[samples.len() / 2]
+}
+
+#[tokio::test]
+async fn test_ssh_tunnel_throughput() {
+    let (manager, known_hosts, _dir) = test_manager();
+    connect(&manager, &known_hosts, "tunnel_throughput_direct", false).await;
+    connect(&manager, &known_hosts, "tunnel_throughput_ssh", true).await;
+
+    // ~16 MB of row data
+    let sql = "SELECT g AS id, repeat('x', 800) AS payload FROM generate_series(1, 20000) g";
+    let _ = timed_query(&manager, "tunnel_throughput_ssh", "SELECT 1").await;
+
+    let (direct, bytes) = timed_query(&manager, "tunnel_throughput_direct", sql).await;
+    let (tunneled, tunneled_bytes) = timed_query(&manager, "tunnel_throughput_ssh", sql).await;
+    assert_eq!(bytes, tunneled_bytes);
+
+    let rate = tunneled_bytes as f64 / tunneled.as_secs_f64() / (1024.0 * 1024.0);
+    println!(
+        "direct: {:?}, tunneled: {:?} ({:.1} MB/s)",
+        direct, tunneled, rate
+    );
+    // Low floor, the local test server only has to beat a stalled forwarder
+    assert!(rate > 5.0, "tunnel throughput only {:.1} MB/s", rate);
+
+    let _ = manager
+        .disconnect("tunnel_throughput_direct".to_string())
+        .await;
+    let _ = manager
+        .disconnect("tunnel_throughput_ssh".to_string())
+        .await;
+}
+
+#[tokio::test]
+async fn test_ssh_tunnel_latency() {
+    let (manager, known_hosts, _dir) = test_manager();
+    connect(&manager, &known_hosts, "tunnel_latency_direct", false).await;
+    connect(&manager, &known_hosts, "tunnel_latency_ssh", true).await;
+
+    let mut direct = Vec::new();
+    let mut tunneled = Vec::new();
+    for _ in 0..50 {
+        direct.push(
+            timed_query(&manager, "tunnel_latency_direct", "SELECT 1")
+                .await
+                .0,
+        );
+        tunneled.push(
+            timed_query(&manager, "tunnel_latency_ssh", "SELECT 1")
+                .await
+                .0,
+        );
+    }
+
+    let direct = median(direct);
+    let tunneled = median(tunneled);
+    println!(
+        "median round trip direct: {:?}, tunneled: {:?}",
+        direct, tunneled
+    );
+    // Generous bound, a sleep-based forwarder would add whole poll intervals
+    assert!(
+        tunneled < direct + Duration::from_millis(20),
+        "tunnel adds too much latency: direct {:?}, tunneled {:?}",
+        direct,
+        tunneled
+    );
+
+    let _ = manager
+        .disconnect("tunnel_latency_direct".to_string())
+        .await;
+    let _ = manager.disconnect("tunnel_latency_ssh".to_string()).await;
+}
+
+#[tokio::test]
+async fn test_ssh_tunnel_shares_one_session() {
+    let (manager, known_hosts, _dir) = test_manager();
+    let id = "tunnel_shared_session";
+    connect(&manager, &known_hosts, id, true).await;
+
+    // Parallel queries open several pooled connections over the same tunnel
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let manager = manager.clone();
+            tokio::spawn(async move {
+                timed_query(&manager, id, "SELECT pg_sleep(0.2)").await;
+            })
+        })
+        .collect();
+    for handle in handles {
+        handle.await.unwrap();
+    }
+
+    let status = manager
+        .get_tunnel_status(id)
+        .await
+        .expect("No tunnel status");
+    assert_eq!(status.state, TunnelState::Connected);
+    assert_eq!(status.reconnects, 0);
+    assert!(status.active_channels >= 1);
+    assert!(status.bytes_sent > 0 && status.bytes_received > 0);
+    assert!(manager.list_tunnels().await.contains_key(id));
+
+    let _ = manager.disconnect(id.to_string()).await;
+    assert!(manager.get_tunnel_status(id).await.is_none());
+}
+
+#[tokio::test]
+async fn test_ssh_tunnel_reports_auth_failure() {
+    let (manager, known_hosts, _dir) = test_manager();
+    let mut config = postgres_config("tunnel_bad_auth", true);
+    let to_trust = config.clone();
+    tokio::task::spawn_blocking(move || trust_ssh_server(&to_trust, &known_hosts))
+        .await
+        .unwrap();
+
+    config.ssh_password = Some("wrong_password".to_string());
+    let err = manager
+        .connect("tunnel_bad_auth".to_string(), config)
+        .await
+        .unwrap_err();
+    assert!(
+        matches!(err, DbError::Ssh(ref msg) if msg.contains("Authentication failed")),
+        "Unexpected error: {}",
+        err
+    );
+    assert!(manager.get_tunnel_status("tunnel_bad_auth").await.is_none());
+}
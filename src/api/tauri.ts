@@ -1,11 +1,15 @@
-import { invoke } from '@tauri-apps/api/core';
+import { Channel, invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type {
   ITauriAPI,
+  ConnectionEvent,
+  ConnectionFolder,
   DbConnection,
   AppSettings,
   AppState,
   HistoryItem,
   IDataRequest,
+  IRowBatch,
   RowUpdate,
   AppSchemaCache,
 } from '../types';
@@ -14,13 +18,36 @@ export const tauriApi: ITauriAPI = {
   // Connections
   connect: (id: string) => invoke('connect', { id }),
   disconnect: (id: string) => invoke('disconnect', { id }),
+  getTunnelStatus: (id: string) => invoke('get_tunnel_status', { id }),
+  listSshTunnels: () => invoke('list_ssh_tunnels'),
+  onConnectionEvent: (handler: (event: ConnectionEvent) => void) =>
+    listen<ConnectionEvent>('connection-state', (e) => handler(e.payload)),
   testConnection: (config: DbConnection, connectionId?: string) =>
     invoke('test_connection', { config, connectionId }),
+  scanSshHostKeys: (config: DbConnection, connectionId?: string) =>
+    invoke('scan_ssh_host_keys', { config, connectionId }),
+  trustSshHostKey: (
+    config: DbConnection,
+    host: string,
+    port: number,
+    fingerprint: string,
+    connectionId?: string,
+  ) => invoke('trust_ssh_host_key', { config, connectionId, host, port, fingerprint }),
 
   // Storage
   getConnections: () => invoke('get_connections'),
   saveConnection: (connection: DbConnection) => invoke('save_connection', { connection }),
+  parseConnectionString: (dsn: string) => invoke('parse_connection_string', { dsn }),
+  getConnectionString: (id: string) => invoke('get_connection_string', { id }),
+  importClientConnections: () => invoke('import_client_connections'),
   deleteConnection: (id: string) => invoke('delete_connection', { id }),
+  moveConnection: (id: string, folderId: string | null, index: number) =>
+    invoke('move_connection', { id, folderId, index }),
+  getFolders: () => invoke('get_folders'),
+  saveFolder: (folder: ConnectionFolder) => invoke('save_folder', { folder }),
+  moveFolder: (id: string, parentId: string | null, index: number) =>
+    invoke('move_folder', { id, parentId, index }),
+  deleteFolder: (id: string) => invoke('delete_folder', { id }),
 
   getSettings: () => invoke('get_settings'),
   saveSettings: (settings: AppSettings) => invoke('save_settings', { settings }),
@@ -33,7 +60,22 @@ export const tauriApi: ITauriAPI = {
   saveHistory: (history: HistoryItem[]) => invoke('save_history', { history }),
 
   // Queries
-  execute: (id: string, sql: string, queryId?: string) => invoke('query', { id, sql, queryId }),
+  execute: (id: string, sql: string, queryId?: string, confirmToken?: string) =>
+    invoke('query', { id, sql, queryId, confirmToken }),
+  executeStream: (
+    id: string,
+    sql: string,
+    onBatch: (batch: IRowBatch) => void,
+    queryId?: string,
+    confirmToken?: string,
+    maxRows?: number,
+  ) => {
+    const channel = new Channel<IRowBatch>();
+    channel.onmessage = onBatch;
+    return invoke('query_stream', { id, sql, queryId, confirmToken, maxRows, onBatch: channel });
+  },
+  executeScript: (id: string, sql: string, stopOnError: boolean, confirmToken?: string) =>
+    invoke('execute_script', { id, sql, stopOnError, confirmToken }),
   cancelQuery: (id: string, queryId: string) => invoke('cancel_query', { id, queryId }),
   getTables: (id: string, dbName?: string) => invoke('get_tables', { id, dbName }),
   getDatabases: (id: string, excludeList?: string) => invoke('get_databases', { id, excludeList }),
@@ -25,6 +25,8 @@ import sidebar from '@/assets/icons/sidebar.svg?raw';
 import panelBottom from '@/assets/icons/panelBottom.svg?raw';
 import square from '@/assets/icons/square.svg?raw';
 import alertCircle from '@/assets/icons/alertCircle.svg?raw';
+import folder from '@/assets/icons/folder.svg?raw';
+import folderPlus from '@/assets/icons/folderPlus.svg?raw';
 
 export const icons: Record<string, string> = {
   plus,
@@ -53,4 +55,6 @@ export const icons: Record<string, string> = {
   panelBottom,
   square,
   alertCircle,
+  folder,
+  folderPlus,
 };
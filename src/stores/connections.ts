@@ -1,6 +1,13 @@
 import { defineStore } from 'pinia';
 import { ref, reactive } from 'vue';
-import { DbConnection, DbConnectionMeta, DbSchema, AppSchemaCache } from '../types';
+import {
+  ConnectionEvent,
+  ConnectionFolder,
+  DbConnection,
+  DbConnectionMeta,
+  DbSchema,
+  AppSchemaCache,
+} from '../types';
 
 // Simple debounce implementation
 function debounce<T extends (...args: unknown[]) => unknown>(fn: T, delay: number): (...args: Parameters<T>) => void {
@@ -16,8 +23,12 @@ function debounce<T extends (...args: unknown[]) => unknown>(fn: T, delay: numbe
 
 export const useConnectionStore = defineStore('connections', () => {
   const savedConnections = ref<DbConnectionMeta[]>([]);
+  // Both lists arrive sorted by their manual order
+  const folders = ref<ConnectionFolder[]>([]);
   const activeId = ref<string | null>(null);
   const activeConnectionIds = ref<Set<string>>(new Set());
+  // Connections the backend is restoring after they dropped
+  const reconnectingIds = ref<Set<string>>(new Set());
 
   // Data Caches
   const tablesCache = reactive<Record<string, string[]>>({});
@@ -72,11 +83,13 @@ export const useConnectionStore = defineStore('connections', () => {
   async function loadFromStorage(): Promise<void> {
     try {
       loading.value = true;
-      const [conns] = await Promise.all([
+      const [conns, folderList] = await Promise.all([
         window.dbApi.getConnections(),
+        window.dbApi.getFolders(),
         syncCache(), // Load cache in parallel
       ]);
       savedConnections.value = conns;
+      folders.value = folderList;
     } catch (e) {
       console.error('Failed to load connections', e);
     } finally {
@@ -105,10 +118,49 @@ export const useConnectionStore = defineStore('connections', () => {
     if (activeId.value === id) activeId.value = null;
   }
 
+  async function moveConnection(id: string, folderId: string | null, index: number): Promise<void> {
+    await window.dbApi.moveConnection(id, folderId, index);
+    await loadFromStorage();
+  }
+
+  async function saveFolder(folder: ConnectionFolder): Promise<void> {
+    if (!folder.id) folder.id = crypto.randomUUID();
+    await window.dbApi.saveFolder(folder);
+    await loadFromStorage();
+  }
+
+  async function moveFolder(id: string, parentId: string | null, index: number): Promise<void> {
+    await window.dbApi.moveFolder(id, parentId, index);
+    await loadFromStorage();
+  }
+
+  async function deleteFolder(id: string): Promise<void> {
+    await window.dbApi.deleteFolder(id);
+    await loadFromStorage();
+  }
+
   function isConnected(id: string): boolean {
     return activeConnectionIds.value.has(id);
   }
 
+  function isReconnecting(id: string): boolean {
+    return reconnectingIds.value.has(id);
+  }
+
+  function onConnectionEvent(event: ConnectionEvent): void {
+    const id = event.connectionId;
+    if (event.state === 'reconnecting') {
+      reconnectingIds.value.add(id);
+      return;
+    }
+    reconnectingIds.value.delete(id);
+    if (event.state === 'connected') {
+      activeConnectionIds.value.add(id);
+    } else {
+      console.error(`Reconnect of ${id} failed:`, event.error);
+    }
+  }
+
   const pendingConnections = new Map<string, Promise<void>>();
 
   async function ensureConnection(targetId: string | null): Promise<void> {
@@ -211,6 +263,7 @@ export const useConnectionStore = defineStore('connections', () => {
       console.error('Disconnect failed', e);
     } finally {
       activeConnectionIds.value.delete(id);
+      reconnectingIds.value.delete(id);
       // const tabStore = (await import('./tabs')).useTabStore();
       // tabStore.resetConnectionState(id);
       const queryStoreModule = await import('./query');
@@ -282,12 +335,16 @@ export const useConnectionStore = defineStore('connections', () => {
 
   // --- INITIALIZATION ---
   loadFromStorage();
+  window.dbApi.onConnectionEvent(onConnectionEvent).catch(console.error);
 
   return {
     savedConnections,
+    folders,
     activeId,
     activeConnectionIds,
+    reconnectingIds,
     isConnected,
+    isReconnecting,
     tablesCache,
     schemaCache,
     loading,
@@ -295,6 +352,10 @@ export const useConnectionStore = defineStore('connections', () => {
     loadFromStorage,
     addConnection,
     deleteConnection,
+    moveConnection,
+    saveFolder,
+    moveFolder,
+    deleteFolder,
     ensureConnection,
     loadTables,
     loadSchema,
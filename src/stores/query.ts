@@ -3,12 +3,17 @@ import { ref } from 'vue';
 import { useTabStore, QueryTab } from './tabs';
 import { useConnectionStore } from './connections';
 import { useHistoryStore } from './history';
+import { useSettingsStore } from './settings';
+import i18n from '../i18n';
+import type { IColumnInfo, IConfirmationRequest, IRowBatch } from '../types';
+import { toRecords } from '../utils/queryResult';
 
 
 export const useQueryStore = defineStore('query', () => {
   const tabStore = useTabStore();
   const connectionStore = useConnectionStore();
   const historyStore = useHistoryStore();
+  const settingsStore = useSettingsStore();
 
   const activeDatabaseCache = ref<Map<string, string | null>>(new Map());
 
@@ -41,6 +46,13 @@ export const useQueryStore = defineStore('query', () => {
     activeDatabaseCache.value.delete(connectionId);
   }
 
+  function confirmDestructive(confirmation: IConfirmationRequest): boolean {
+    const message = i18n.global.t('query.confirmDestructive', {
+      reasons: confirmation.reasons.join(', '),
+    });
+    return window.confirm(message);
+  }
+
   async function runQuery(tabId?: number): Promise<void> {
     // If tabId provided, use it, otherwise use current
     const targetTab = tabId ? tabStore.tabs.find(t => t.id === tabId) : tabStore.currentTab;
@@ -51,6 +63,20 @@ export const useQueryStore = defineStore('query', () => {
     const connId = tab.connectionId;
     const dbName = tab.database;
 
+    // Rows come in batches while the query runs; the first one also brings the columns
+    // and replaces the previous result
+    let columns: IColumnInfo[] = [];
+    let received = false;
+    const onBatch = (batch: IRowBatch): void => {
+      if (batch.columns) {
+        columns = batch.columns;
+        tab.colDefs = toRecords({ columns, rows: [] }).colDefs;
+        tab.rows = [];
+      }
+      tab.rows.push(...toRecords({ columns, rows: batch.rows }).rows);
+      received = true;
+    };
+
     // Helper to execute the core query logic
     const execute = async (
       forceDbSwitch = false,
@@ -87,13 +113,38 @@ export const useQueryStore = defineStore('query', () => {
         finalSql += ` LIMIT ${tab.pagination.limit} OFFSET ${tab.pagination.offset}`;
       }
 
-      const res = await window.dbApi.execute(connId, finalSql, tab.currentQueryId);
+      const maxRows = settingsStore.maxResultRows;
+      let res = await window.dbApi.executeStream(
+        connId,
+        finalSql,
+        onBatch,
+        tab.currentQueryId,
+        undefined,
+        maxRows,
+      );
+
+      // Production connections hold destructive statements back until confirmed
+      if (res.confirmation) {
+        if (confirmDestructive(res.confirmation)) {
+          res = await window.dbApi.executeStream(
+            connId,
+            finalSql,
+            onBatch,
+            tab.currentQueryId,
+            res.confirmation.token,
+            maxRows,
+          );
+        } else {
+          res = { ...res, error: i18n.global.t('query.destructiveCancelled') };
+        }
+      }
       return { res, tableName, isSimpleSelect }; // Return needed data
     };
 
     try {
       tab.loading = true;
       tab.error = null;
+      tab.statements = undefined;
       // Generate ID
       tab.currentQueryId = crypto.randomUUID();
 
@@ -124,14 +175,18 @@ export const useQueryStore = defineStore('query', () => {
         tab.error = res.error;
         historyStore.addEntry(tab.sql, 'error', 0, connId);
       } else {
-        // Columns
-        tab.colDefs = res.columns.map((col: string) => ({
-          field: col,
-          headerName: col,
-        }));
-
-        tab.rows = res.rows as Record<string, unknown>[];
-        tab.meta = { duration: res.duration };
+        // The final result has the full column metadata, the rows are already in place
+        tab.colDefs = toRecords({ columns: res.columns, rows: [] }).colDefs;
+        if (!received) {
+          tab.rows = [];
+        }
+        tab.meta = {
+          duration: res.duration,
+          syntheticCommandTag: res.syntheticCommandTag,
+          rowsAffected: res.rowsAffected,
+          lastInsertId: res.lastInsertId,
+          truncated: res.truncated,
+        };
 
         historyStore.addEntry(tab.sql, 'success', res.duration, connId);
 
@@ -143,17 +198,15 @@ export const useQueryStore = defineStore('query', () => {
               // Count query usually fast, no need for cancellation ID?
               const countRes = await window.dbApi.execute(connId, countSql);
               if (countRes.rows.length > 0) {
-                const row = countRes.rows[0] as Record<string, unknown>;
-                const val = Object.values(row)[0];
-                tab.pagination.total = Number(val);
+                tab.pagination.total = Number(countRes.rows[0][0]);
               }
             } catch (e) {
               console.error('Count failed', e);
             }
           }
         } else {
-          if (res.rows.length < tab.pagination.limit) {
-            tab.pagination.total = tab.pagination.offset + res.rows.length;
+          if (tab.rows.length < tab.pagination.limit) {
+            tab.pagination.total = tab.pagination.offset + tab.rows.length;
           } else {
             tab.pagination.total = null;
           }
@@ -176,6 +229,82 @@ export const useQueryStore = defineStore('query', () => {
     }
   }
 
+  // Runs every statement of the editor buffer, each on its own, and shows the last
+  // result set in the grid
+  async function runScript(tabId?: number, stopOnError = true): Promise<void> {
+    const targetTab = tabId ? tabStore.tabs.find(t => t.id === tabId) : tabStore.currentTab;
+    const tab = targetTab as QueryTab | undefined;
+
+    if (!tab || tab.type !== 'query' || tab.connectionId === null) return;
+
+    const connId = tab.connectionId;
+    const dbName = tab.database;
+
+    // Rows come in batches while the query runs; the first one also brings the columns
+    // and replaces the previous result
+    let columns: IColumnInfo[] = [];
+    let received = false;
+    const onBatch = (batch: IRowBatch): void => {
+      if (batch.columns) {
+        columns = batch.columns;
+        tab.colDefs = toRecords({ columns, rows: [] }).colDefs;
+        tab.rows = [];
+      }
+      tab.rows.push(...toRecords({ columns, rows: batch.rows }).rows);
+      received = true;
+    };
+
+    try {
+      tab.loading = true;
+      tab.error = null;
+      tab.statements = undefined;
+      await connectionStore.ensureConnection(connId);
+
+      if (dbName && activeDatabaseCache.value.get(connId) !== dbName) {
+        await window.dbApi.setActiveDatabase(connId, dbName);
+        activeDatabaseCache.value.set(connId, dbName);
+      }
+
+      let res = await window.dbApi.executeScript(connId, tab.sql, stopOnError);
+      if (res.confirmation) {
+        if (!confirmDestructive(res.confirmation)) {
+          tab.error = i18n.global.t('query.destructiveCancelled');
+          return;
+        }
+        res = await window.dbApi.executeScript(
+          connId,
+          tab.sql,
+          stopOnError,
+          res.confirmation.token,
+        );
+      }
+
+      const statements = res.statements;
+      const duration = statements.reduce((sum, s) => sum + s.duration, 0);
+      const failed = statements.find(s => s.status === 'error');
+      const lastResultSet = [...statements].reverse().find(s => s.result.columns.length > 0);
+
+      tab.statements = statements;
+      const { colDefs, rows } = lastResultSet
+        ? toRecords(lastResultSet.result)
+        : { colDefs: [], rows: [] };
+      tab.colDefs = colDefs;
+      tab.rows = rows;
+      tab.meta = { duration };
+      tab.pagination.offset = 0;
+      tab.pagination.total = tab.rows.length;
+      tab.error = failed?.result.error ?? null;
+
+      historyStore.addEntry(tab.sql, failed ? 'error' : 'success', duration, connId);
+    } catch (e) {
+      tab.error = e instanceof Error ? e.message : String(e);
+      historyStore.addEntry(tab.sql, 'error', 0, connId);
+    } finally {
+      tab.loading = false;
+      tabStore.saveToStorage();
+    }
+  }
+
   async function cancelQuery(tabId?: number): Promise<void> {
     const targetTab = tabId ? tabStore.tabs.find(t => t.id === tabId) : tabStore.currentTab;
     const tab = targetTab as QueryTab | undefined;
@@ -191,6 +320,7 @@ export const useQueryStore = defineStore('query', () => {
 
   return {
     runQuery,
+    runScript,
     cancelQuery,
     resetConnectionState,
     activeDatabaseCache, // Expose if needed elsewhere
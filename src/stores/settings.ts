@@ -6,6 +6,8 @@ export const useSettingsStore = defineStore('settings', () => {
   const currentThemeId = ref('atom-one-dark');
   const fontSize = ref(14);
   const language = ref('en');
+  // Rows a query may return before the rest are cut off
+  const maxResultRows = ref(100000);
 
   const activeTheme = computed(() => themes.find((t) => t.id === currentThemeId.value) || themes[0]);
 
@@ -26,6 +28,7 @@ export const useSettingsStore = defineStore('settings', () => {
         theme: currentThemeId.value,
         locale: language.value,
         fontSize: fontSize.value,
+        maxResultRows: maxResultRows.value,
       });
     } catch (e) {
       console.error('Failed to save settings:', e);
@@ -37,6 +40,12 @@ export const useSettingsStore = defineStore('settings', () => {
     saveSettings();
   }
 
+  function setMaxResultRows(rows: number): void {
+    if (!Number.isInteger(rows) || rows < 1) return;
+    maxResultRows.value = rows;
+    saveSettings();
+  }
+
   function initTheme(): void {
     applyTheme();
   }
@@ -68,6 +77,10 @@ export const useSettingsStore = defineStore('settings', () => {
         fontSize.value = settings.fontSize;
       }
 
+      if (settings.maxResultRows) {
+        maxResultRows.value = settings.maxResultRows;
+      }
+
       if (settings.locale) {
         language.value = settings.locale;
         setLanguage(settings.locale); // activates i18n
@@ -91,5 +104,7 @@ export const useSettingsStore = defineStore('settings', () => {
     setLanguage,
     setTheme,
     setFontSize,
+    maxResultRows,
+    setMaxResultRows,
   };
 });
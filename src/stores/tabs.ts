@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia';
 import { ref, computed, watch } from 'vue';
 import { useConnectionStore } from './connections';
+import type { IColumnInfo, IStatementResult } from '../types';
 
 import i18n from '../i18n';
 
@@ -16,8 +17,14 @@ export interface QueryTab extends BaseTab {
   sql: string
   currentQueryId?: string
   rows: Record<string, unknown>[]
-  colDefs: { field: string; headerName?: string }[]
-  meta: { duration: number } | null
+  colDefs: { field: string; headerName?: string; column?: IColumnInfo }[]
+  meta: {
+    duration: number
+    syntheticCommandTag?: string | null
+    rowsAffected?: number | null
+    lastInsertId?: number | null
+    truncated?: boolean
+  } | null
   pagination: {
     limit: number
     offset: number
@@ -29,6 +36,8 @@ export interface QueryTab extends BaseTab {
   originalRows: Map<string, Record<string, unknown>>
   loading?: boolean
   error?: string | null
+  // Per-statement outcome of the last script run
+  statements?: IStatementResult[]
 }
 
 export interface SettingsTab extends BaseTab {
@@ -1,6 +1,48 @@
+export type SslMode = 'disable' | 'prefer' | 'require' | 'verify-ca' | 'verify-full'
+export type ProxyType = 'socks5' | 'http'
+export type Environment = 'dev' | 'staging' | 'prod'
+
+export interface SshJumpHost {
+  host: string
+  port?: string
+  user?: string
+  password?: string
+  keyPath?: string
+}
+
+export interface SessionVariable {
+  name: string
+  value: string
+}
+
+export interface SshHostKey {
+  host: string
+  port: number
+  keyType: string
+  fingerprint: string
+  status: 'trusted' | 'unknown' | 'changed'
+}
+
+export interface TunnelStatus {
+  state: 'connecting' | 'connected' | 'reconnecting' | 'failed' | 'closed'
+  localPort: number
+  activeChannels: number
+  reconnects: number
+  bytesSent: number
+  bytesReceived: number
+  lastError: string | null
+}
+
+export interface ConnectionEvent {
+  connectionId: string
+  state: 'reconnecting' | 'connected' | 'failed'
+  attempt: number
+  error: string | null
+}
+
 export interface DbConnection {
   id: string
-  type: 'mysql' | 'postgres' | 'clickhouse'
+  type: 'mysql' | 'postgres' | 'clickhouse' | 'sqlite' | 'duckdb'
   name: string
   host: string
   port: string
@@ -8,34 +50,102 @@ export interface DbConnection {
   password?: string
   database: string
   excludeList?: string
+  readOnly?: boolean
+  environment?: Environment
+  folderId?: string | null
+  color?: string | null
+  tags?: string[] | null
+  notes?: string | null
+  sortOrder?: number | null
   useSsh?: boolean
   sshHost?: string
   sshPort?: string
   sshUser?: string
   sshPassword?: string
   sshKeyPath?: string
+  sshKeyPassphrase?: string
+  sshPrivateKey?: string
+  sshJumpHosts?: SshJumpHost[]
+  useProxy?: boolean
+  proxyType?: ProxyType
+  proxyHost?: string
+  proxyPort?: string
+  proxyUser?: string
+  proxyPassword?: string
+  sslMode?: SslMode
+  sslCaPath?: string
+  sslCertPath?: string
+  sslKeyPath?: string
+  sslAcceptInvalidCerts?: boolean
+  connectTimeout?: number
+  applicationName?: string
+  maxConnections?: number
+  acquireTimeout?: number
+  idleTimeout?: number
+  statementTimeout?: number
+  httpTimeout?: number
+  sessionVariables?: SessionVariable[]
+  initScript?: string
 }
 
 export interface DbConnectionMeta {
   id: string
-  type: 'mysql' | 'postgres' | 'clickhouse'
+  type: 'mysql' | 'postgres' | 'clickhouse' | 'sqlite' | 'duckdb'
   name: string
   host: string
   port: string
   user: string
   database: string
   excludeList?: string
+  readOnly?: boolean
+  environment?: Environment
+  folderId?: string | null
+  color?: string | null
+  tags?: string[] | null
+  notes?: string | null
+  sortOrder?: number | null
   useSsh?: boolean
   sshHost?: string
   sshPort?: string
   sshUser?: string
   sshKeyPath?: string
+  hasSshPrivateKey?: boolean
+  hasSshKeyPassphrase?: boolean
+  sshJumpHosts?: SshJumpHost[]
+  useProxy?: boolean
+  proxyType?: ProxyType
+  proxyHost?: string
+  proxyPort?: string
+  proxyUser?: string
+  sslMode?: SslMode
+  sslCaPath?: string
+  sslCertPath?: string
+  sslKeyPath?: string
+  sslAcceptInvalidCerts?: boolean
+  connectTimeout?: number
+  applicationName?: string
+  maxConnections?: number
+  acquireTimeout?: number
+  idleTimeout?: number
+  statementTimeout?: number
+  httpTimeout?: number
+  sessionVariables?: SessionVariable[]
+  initScript?: string
+}
+
+export interface ConnectionFolder {
+  id: string
+  name: string
+  parentId?: string | null
+  color?: string | null
+  sortOrder?: number | null
 }
 
 export interface AppSettings {
   theme: string
   locale: string
   fontSize: number
+  maxResultRows?: number
 }
 
 export interface HistoryItem {
@@ -106,11 +216,72 @@ export interface AppState {
   }
 }
 
+// Database types grouped by how values are displayed and edited
+export type LogicalType =
+  | 'boolean'
+  | 'integer'
+  | 'decimal'
+  | 'float'
+  | 'text'
+  | 'json'
+  | 'uuid'
+  | 'date'
+  | 'time'
+  | 'timestamp'
+  | 'binary'
+  | 'array'
+  | 'other'
+
+export interface IColumnInfo {
+  name: string
+  typeName: string
+  logicalType: LogicalType
+  nullable?: boolean | null
+  sourceTable?: string | null
+  sourceColumn?: string | null
+}
+
 export interface IDbResult {
-  rows: unknown[]
-  columns: string[]
+  // One value per column, in the order of `columns`. Empty for streamed queries, whose
+  // rows arrive as batches.
+  rows: unknown[][]
+  columns: IColumnInfo[]
   error?: string
   duration: number
+  confirmation?: IConfirmationRequest
+  rowsAffected?: number | null
+  lastInsertId?: number | null
+  // Postgres only, e.g. `UPDATE 42` or `CREATE TABLE`. Rebuilt from the statement, not
+  // the tag the server sent
+  syntheticCommandTag?: string | null
+  // Rows past the row cap were cut off
+  truncated?: boolean
+}
+
+// Rows of a streamed query; the first batch also carries the columns
+export interface IRowBatch {
+  columns?: IColumnInfo[] | null
+  rows: unknown[][]
+}
+
+export interface IConfirmationRequest {
+  token: string
+  reasons: string[]
+}
+
+// 'skipped' statements didn't run because an earlier one failed
+export type StatementStatus = 'success' | 'error' | 'skipped'
+
+export interface IStatementResult {
+  sql: string
+  status: StatementStatus
+  duration: number
+  result: IDbResult
+}
+
+export interface IScriptResult {
+  statements: IStatementResult[]
+  confirmation?: IConfirmationRequest
 }
 
 export interface IDataRequest {
@@ -140,12 +311,31 @@ export interface ITauriAPI {
   // Connections
   connect: (id: string) => Promise<string>
   disconnect: (id: string) => Promise<void>
+  getTunnelStatus: (id: string) => Promise<TunnelStatus | null>
+  listSshTunnels: () => Promise<Record<string, TunnelStatus>>
+  onConnectionEvent: (handler: (event: ConnectionEvent) => void) => Promise<() => void>
   testConnection: (config: DbConnection, connectionId?: string) => Promise<string>
+  scanSshHostKeys: (config: DbConnection, connectionId?: string) => Promise<SshHostKey[]>
+  trustSshHostKey: (
+    config: DbConnection,
+    host: string,
+    port: number,
+    fingerprint: string,
+    connectionId?: string,
+  ) => Promise<void>
 
   // Storage
   getConnections: () => Promise<DbConnectionMeta[]>
   saveConnection: (connection: DbConnection) => Promise<void>
+  parseConnectionString: (dsn: string) => Promise<DbConnection>
+  getConnectionString: (id: string) => Promise<string>
+  importClientConnections: () => Promise<DbConnectionMeta[]>
   deleteConnection: (id: string) => Promise<void>
+  moveConnection: (id: string, folderId: string | null, index: number) => Promise<void>
+  getFolders: () => Promise<ConnectionFolder[]>
+  saveFolder: (folder: ConnectionFolder) => Promise<void>
+  moveFolder: (id: string, parentId: string | null, index: number) => Promise<void>
+  deleteFolder: (id: string) => Promise<void>
 
   getSettings: () => Promise<AppSettings>
   saveSettings: (settings: AppSettings) => Promise<void>
@@ -158,7 +348,26 @@ export interface ITauriAPI {
   saveHistory: (history: HistoryItem[]) => Promise<void>
 
   // Queries
-  execute: (id: string, sql: string, queryId?: string) => Promise<IDbResult>
+  execute: (
+    id: string,
+    sql: string,
+    queryId?: string,
+    confirmToken?: string,
+  ) => Promise<IDbResult>
+  executeStream: (
+    id: string,
+    sql: string,
+    onBatch: (batch: IRowBatch) => void,
+    queryId?: string,
+    confirmToken?: string,
+    maxRows?: number,
+  ) => Promise<IDbResult>
+  executeScript: (
+    id: string,
+    sql: string,
+    stopOnError: boolean,
+    confirmToken?: string,
+  ) => Promise<IScriptResult>
   cancelQuery: (id: string, queryId: string) => Promise<void>
   getTables: (id: string, dbName?: string) => Promise<string[]>
   getDatabases: (id: string, excludeList?: string) => Promise<string[]>
@@ -206,4 +415,5 @@ export interface DashboardMetrics {
     duration: string
     query: string
   }>
+  extra?: Record<string, string>
 }
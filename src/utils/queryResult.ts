@@ -0,0 +1,35 @@
+import type { IColumnInfo, IDbResult } from '../types';
+
+// Column keys with repeated names numbered (`id`, `id_2`), the same way exports do
+export function uniqueColumnNames(columns: string[]): string[] {
+  const names: string[] = [];
+  for (const column of columns) {
+    let name = column;
+    for (let n = 2; names.includes(name); n++) {
+      name = `${column}_${n}`;
+    }
+    names.push(name);
+  }
+  return names;
+}
+
+// The grid works with objects, so positional rows are keyed by unique column names
+export function toRecords(result: Pick<IDbResult, 'columns' | 'rows'>): {
+  colDefs: { field: string; headerName: string; column: IColumnInfo }[]
+  rows: Record<string, unknown>[]
+} {
+  const fields = uniqueColumnNames(result.columns.map((c) => c.name));
+  return {
+    colDefs: fields.map((field, i) => ({
+      field,
+      headerName: result.columns[i].name,
+      column: result.columns[i],
+    })),
+    rows: result.rows.map((row) => Object.fromEntries(fields.map((field, i) => [field, row[i]]))),
+  };
+}
+
+// Numbers are right-aligned in the grid
+export function isNumericColumn(column?: IColumnInfo): boolean {
+  return ['integer', 'decimal', 'float'].includes(column?.logicalType ?? '');
+}